@@ -0,0 +1,79 @@
+//! Measures `width` and `truncate_to_width` on log lines.
+//!
+//! Every corpus is measured as it is and with `'\u{B7}'` appended to each
+//! line, which fails the printable ASCII check at the very end and so
+//! measures the character by character path that all strings took before.
+//! Set `UNICODE_WIDTH_BENCH_LOG` to a log file to add it as a corpus:
+//!
+//! ```text
+//! UNICODE_WIDTH_BENCH_LOG=/var/log/syslog cargo bench -p unicode_width
+//! ```
+
+use std::{env, fs, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use unicode_width::{truncate_to_width, UnicodeWidthStr};
+
+const ACCESS_LOG: &str = r#"203.0.113.7 - - [19/Oct/2026:10:02:11 +0000] "GET /api/v1/items?page=2&limit=50 HTTP/1.1" 200 5123 "-" "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0"
+198.51.100.23 - - [19/Oct/2026:10:02:12 +0000] "POST /api/v1/login HTTP/1.1" 401 87 "https://example.com/login" "curl/8.10.1"
+203.0.113.7 - - [19/Oct/2026:10:02:12 +0000] "GET /static/app.3f9c1e.js HTTP/2.0" 304 0 "https://example.com/" "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_6) AppleWebKit/605.1.15"
+192.0.2.140 - - [19/Oct/2026:10:02:14 +0000] "GET /healthz HTTP/1.1" 200 2 "-" "kube-probe/1.31""#;
+
+const APP_LOG: &str = "2026-10-19T10:02:11.512Z INFO  worker{id=3}: job finished queue=mail attempts=1 elapsed=182ms
+2026-10-19T10:02:11.730Z WARN  db::pool: slow query (1204ms): SELECT id, name FROM users WHERE last_login < $1
+2026-10-19T10:02:12.004Z INFO  주문 처리 완료 order_id=81723 고객=김민지 금액=₩45,000
+2026-10-19T10:02:12.118Z ERROR 결제 실패 ✗ order_id=81724 사유=\"카드 한도 초과\" 🙁
+2026-10-19T10:02:13.402Z INFO  ユーザー登録 user=田中 plan=pro 👍🏽";
+
+fn corpora() -> Vec<(String, Vec<String>)> {
+    let lines = |text: &str| text.lines().map(str::to_owned).collect::<Vec<_>>();
+    let mut corpora = vec![
+        ("access".to_owned(), lines(ACCESS_LOG)),
+        ("app".to_owned(), lines(APP_LOG)),
+    ];
+    if let Some(path) = env::var_os("UNICODE_WIDTH_BENCH_LOG") {
+        let text = fs::read(&path).expect("cannot read UNICODE_WIDTH_BENCH_LOG");
+        corpora.push(("file".to_owned(), lines(&String::from_utf8_lossy(&text))));
+    }
+    corpora
+}
+
+fn bench_width(c: &mut Criterion) {
+    let mut group = c.benchmark_group("width");
+    for (name, lines) in corpora() {
+        let fallback: Vec<String> = lines.iter().map(|line| format!("{line}\u{B7}")).collect();
+        for (path, lines) in [("as_is", &lines), ("fallback", &fallback)] {
+            let bytes: usize = lines.iter().map(String::len).sum();
+            group.throughput(Throughput::Bytes(bytes as u64));
+            group.bench_with_input(BenchmarkId::new(path, &name), lines, |b, lines| {
+                b.iter(|| {
+                    lines
+                        .iter()
+                        .map(|line| black_box(line.as_str()).width())
+                        .sum::<usize>()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_truncate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("truncate_to_width");
+    for (name, lines) in corpora() {
+        let bytes: usize = lines.iter().map(String::len).sum();
+        group.throughput(Throughput::Bytes(bytes as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &lines, |b, lines| {
+            b.iter(|| {
+                lines
+                    .iter()
+                    .map(|line| truncate_to_width(black_box(line), 80, Some("…")).width())
+                    .sum::<usize>()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_width, bench_truncate);
+criterion_main!(benches);
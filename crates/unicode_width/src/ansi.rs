@@ -0,0 +1,154 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::tables;
+
+/// A piece of a string split by [`ansi_segments`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnsiSegment<'a> {
+    /// Text shown on the terminal.
+    Text(&'a str),
+    /// A terminal escape sequence, which takes up no columns.
+    Escape(&'a str),
+}
+
+/// Iterator over the text and escape sequences of a string, created by
+/// [`ansi_segments`].
+#[derive(Clone, Debug)]
+pub struct AnsiSegments<'a> {
+    s: &'a str,
+}
+
+/// Splits `s` into text and the terminal escape sequences between it.
+///
+/// Recognised sequences are CSI (such as the SGR colour codes `ESC [ 31 m`),
+/// OSC (window titles and `OSC 8` hyperlinks), the DCS, SOS, PM and APC
+/// strings, and other `ESC`-prefixed escapes. The 8-bit forms of CSI and
+/// OSC are recognised as well. A sequence cut off by the end of the string
+/// runs to the end.
+///
+/// ```rust
+/// use unicode_width::{ansi_segments, AnsiSegment};
+///
+/// let segments: Vec<_> = ansi_segments("\x1b[1mbold\x1b[0m").collect();
+/// assert_eq!(
+///     segments,
+///     [
+///         AnsiSegment::Escape("\x1b[1m"),
+///         AnsiSegment::Text("bold"),
+///         AnsiSegment::Escape("\x1b[0m"),
+///     ]
+/// );
+/// ```
+pub fn ansi_segments(s: &str) -> AnsiSegments<'_> {
+    AnsiSegments { s }
+}
+
+fn starts_escape(c: char) -> bool {
+    matches!(c, '\u{1B}' | '\u{9B}' | '\u{9D}')
+}
+
+/// Length of the control string at the start of `s`, up to and including
+/// its BEL or ST terminator.
+fn string_len(s: &str) -> usize {
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\u{7}' | '\u{9C}' => return i + c.len_utf8(),
+            '\u{1B}' => {
+                return match chars.peek() {
+                    Some(&(j, '\\')) => j + 1,
+                    _ => i,
+                }
+            }
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// Length of the CSI parameters, intermediates and final byte at the start
+/// of `s`.
+fn csi_len(s: &str) -> usize {
+    for (i, c) in s.char_indices() {
+        match c {
+            '\u{20}'..='\u{3F}' => {}
+            '\u{40}'..='\u{7E}' => return i + 1,
+            _ => return i,
+        }
+    }
+    s.len()
+}
+
+/// Length of the escape sequence starting with the ESC, CSI or OSC at the
+/// start of `s`.
+fn escape_len(s: &str) -> usize {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some('\u{9B}') => 2 + csi_len(&s[2..]),
+        Some('\u{9D}') => 2 + string_len(&s[2..]),
+        _ => match chars.next() {
+            Some('[') => 2 + csi_len(&s[2..]),
+            Some(']' | 'P' | 'X' | '^' | '_') => 2 + string_len(&s[2..]),
+            Some('\u{20}'..='\u{2F}') => {
+                // Intermediates, then the final byte
+                let rest = &s[1..];
+                let end = rest
+                    .find(|c: char| !matches!(c, '\u{20}'..='\u{2F}'))
+                    .unwrap_or(rest.len());
+                match rest[end..].chars().next() {
+                    Some('\u{30}'..='\u{7E}') => 2 + end,
+                    _ => 1 + end,
+                }
+            }
+            Some('\u{30}'..='\u{7E}') => 2,
+            _ => 1,
+        },
+    }
+}
+
+impl<'a> Iterator for AnsiSegments<'a> {
+    type Item = AnsiSegment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.s.chars().next()?;
+        let (segment, rest) = if starts_escape(first) {
+            let (escape, rest) = self.s.split_at(escape_len(self.s));
+            (AnsiSegment::Escape(escape), rest)
+        } else {
+            let end = self.s.find(starts_escape).unwrap_or(self.s.len());
+            let (text, rest) = self.s.split_at(end);
+            (AnsiSegment::Text(text), rest)
+        };
+        self.s = rest;
+        Some(segment)
+    }
+}
+
+impl core::iter::FusedIterator for AnsiSegments<'_> {}
+
+/// The width of the text of `s` measured with `width`, skipping escapes.
+pub(crate) fn width_ansi_by(s: &str, width: fn(&str) -> usize) -> usize {
+    ansi_segments(s)
+        .map(|segment| match segment {
+            AnsiSegment::Text(text) => width(text),
+            AnsiSegment::Escape(_) => 0,
+        })
+        .sum()
+}
+
+pub(crate) fn str_width_ansi(s: &str) -> usize {
+    width_ansi_by(s, tables::str_width)
+}
+
+#[cfg(feature = "cjk")]
+pub(crate) fn str_width_ansi_cjk(s: &str) -> usize {
+    width_ansi_by(s, tables::str_width_cjk)
+}
@@ -0,0 +1,67 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::tables;
+
+/// How [`width_bytes`] measures bytes that are not valid UTF-8.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InvalidWidth {
+    /// Each invalid sequence is shown as one `'\u{FFFD}'`, 1 column wide,
+    /// as [`String::from_utf8_lossy`] replaces them.
+    ///
+    /// [`String::from_utf8_lossy`]: https://doc.rust-lang.org/std/string/struct.String.html#method.from_utf8_lossy
+    #[default]
+    Replacement,
+    /// Each invalid byte is this many columns wide: `PerByte(4)` for bytes
+    /// shown as `\xNN` escapes, `PerByte(1)` for a single glyph per byte.
+    PerByte(usize),
+}
+
+impl InvalidWidth {
+    /// The width of `invalid`, one invalid sequence of at most 3 bytes.
+    fn width(self, invalid: &[u8]) -> usize {
+        match self {
+            InvalidWidth::Replacement => usize::from(!invalid.is_empty()),
+            InvalidWidth::PerByte(width) => invalid.len() * width,
+        }
+    }
+}
+
+fn width_bytes_by(bytes: &[u8], invalid: InvalidWidth, width: fn(&str) -> usize) -> usize {
+    bytes
+        .utf8_chunks()
+        .map(|chunk| width(chunk.valid()) + invalid.width(chunk.invalid()))
+        .sum()
+}
+
+/// Returns the displayed width in columns of `bytes`, which may contain
+/// invalid UTF-8, measuring invalid sequences according to `invalid`.
+///
+/// The valid text between invalid sequences is measured as by
+/// [`width`](crate::UnicodeWidthStr::width), so valid UTF-8 has the same
+/// width as the `str` it decodes to.
+///
+/// ```rust
+/// use unicode_width::{width_bytes, InvalidWidth};
+///
+/// let line = b"\xED\x95\x9C caf\xE9";
+/// assert_eq!(width_bytes(line, InvalidWidth::Replacement), 7);
+/// assert_eq!(width_bytes(line, InvalidWidth::PerByte(4)), 10);
+/// ```
+pub fn width_bytes(bytes: &[u8], invalid: InvalidWidth) -> usize {
+    width_bytes_by(bytes, invalid, tables::str_width)
+}
+
+/// Like [`width_bytes`], measuring valid text with
+/// [`width_cjk`](crate::UnicodeWidthStr::width_cjk).
+#[cfg(feature = "cjk")]
+pub fn width_bytes_cjk(bytes: &[u8], invalid: InvalidWidth) -> usize {
+    width_bytes_by(bytes, invalid, tables::str_width_cjk)
+}
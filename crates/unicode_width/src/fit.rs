@@ -0,0 +1,360 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt;
+
+#[cfg(feature = "cjk")]
+use crate::ansi::str_width_ansi_cjk;
+use crate::{
+    ansi::{ansi_segments, str_width_ansi, AnsiSegment},
+    tables, Profile, WidthIndices,
+};
+
+/// A string cut down to a number of columns, returned by [`truncate_to_width`].
+///
+/// Displays as the kept text followed by the suffix, if the string was cut.
+/// When cut by [`truncate_to_width_ansi`], the escape sequences from the
+/// part that was cut off follow, so that colours are still reset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Truncated<'a> {
+    text: &'a str,
+    suffix: &'a str,
+    /// Cut off text whose escape sequences are kept, if any
+    escapes: &'a str,
+    width: usize,
+    truncated: bool,
+}
+
+impl<'a> Truncated<'a> {
+    /// The part of the string that was kept.
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// The suffix shown after the text, empty if nothing was cut.
+    pub fn suffix(&self) -> &'a str {
+        self.suffix
+    }
+
+    /// Whether any of the string was cut off.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// The width of the text and suffix together.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl fmt::Display for Truncated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text)?;
+        f.write_str(self.suffix)?;
+        for segment in ansi_segments(self.escapes) {
+            if let AnsiSegment::Escape(escape) = segment {
+                f.write_str(escape)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where [`pad_to_width`] puts the text within the padded field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    /// Text first, padding on the right.
+    Left,
+    /// Padding on the left, text last.
+    Right,
+    /// Padding split between both sides, the odd column going on the right.
+    Center,
+}
+
+/// A string padded with spaces to a number of columns, returned by
+/// [`pad_to_width`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Padded<'a> {
+    text: &'a str,
+    text_width: usize,
+    left: usize,
+    right: usize,
+}
+
+impl Padded<'_> {
+    /// The width of the text and padding together.
+    pub fn width(&self) -> usize {
+        self.left + self.text_width + self.right
+    }
+}
+
+impl fmt::Display for Padded<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for _ in 0..self.left {
+            f.write_str(" ")?;
+        }
+        f.write_str(self.text)?;
+        for _ in 0..self.right {
+            f.write_str(" ")?;
+        }
+        Ok(())
+    }
+}
+
+/// The longest prefix of `s` at most `max_width` columns wide that ends on
+/// a cluster boundary, and its width.
+fn fit(s: &str, max_width: usize, width: fn(&str) -> usize, profile: Profile) -> (&str, usize) {
+    if tables::is_printable_ascii(s) {
+        let end = s.len().min(max_width);
+        return (&s[..end], end);
+    }
+    let mut end = 0;
+    let mut total = 0;
+    for (offset, cluster, w) in WidthIndices::for_profile(s, width, profile) {
+        if total + w > max_width {
+            break;
+        }
+        end = offset + cluster.len();
+        total += w;
+    }
+    (&s[..end], total)
+}
+
+/// Like `fit`, skipping over escape sequences, which are kept up to the
+/// first text that doesn't fit.
+fn fit_ansi(
+    s: &str,
+    max_width: usize,
+    width: fn(&str) -> usize,
+    profile: Profile,
+) -> (&str, usize) {
+    let mut end = 0;
+    let mut total = 0;
+    for segment in ansi_segments(s) {
+        match segment {
+            AnsiSegment::Escape(escape) => end += escape.len(),
+            AnsiSegment::Text(text) => {
+                let (kept, w) = fit(text, max_width - total, width, profile);
+                end += kept.len();
+                total += w;
+                if kept.len() < text.len() {
+                    break;
+                }
+            }
+        }
+    }
+    (&s[..end], total)
+}
+
+fn truncate_by<'a>(
+    s: &'a str,
+    max_width: usize,
+    suffix: Option<&'a str>,
+    width: fn(&str) -> usize,
+    profile: Profile,
+    ansi: bool,
+) -> Truncated<'a> {
+    let fit = if ansi { fit_ansi } else { fit };
+    let (text, text_width) = fit(s, max_width, width, profile);
+    if text.len() == s.len() {
+        return Truncated {
+            text: s,
+            suffix: "",
+            escapes: "",
+            width: text_width,
+            truncated: false,
+        };
+    }
+    let (suffix, suffix_width) = fit(suffix.unwrap_or(""), max_width, width, profile);
+    let (text, text_width) = fit(s, max_width - suffix_width, width, profile);
+    Truncated {
+        text,
+        suffix,
+        escapes: if ansi { &s[text.len()..] } else { "" },
+        width: text_width + suffix_width,
+        truncated: true,
+    }
+}
+
+fn pad_by(s: &str, min_width: usize, align: Align, width: fn(&str) -> usize) -> Padded<'_> {
+    let text_width = width(s);
+    let fill = min_width.saturating_sub(text_width);
+    let (left, right) = match align {
+        Align::Left => (0, fill),
+        Align::Right => (fill, 0),
+        Align::Center => (fill / 2, fill - fill / 2),
+    };
+    Padded {
+        text: s,
+        text_width,
+        left,
+        right,
+    }
+}
+
+/// Cuts `s` down to at most `max_width` columns, keeping whole clusters
+/// (see [`WidthIndices`]) so that no emoji or Hangul syllable is split.
+///
+/// If `s` doesn't fit, `suffix` is shown after what is kept and counts
+/// towards `max_width`; a suffix that is itself too wide is cut as well.
+///
+/// ```rust
+/// use unicode_width::truncate_to_width;
+///
+/// assert_eq!(truncate_to_width("한국어", 5, None).to_string(), "한국");
+/// assert_eq!(truncate_to_width("한국어", 5, Some("…")).to_string(), "한국…");
+/// assert_eq!(truncate_to_width("한국어", 6, Some("…")).to_string(), "한국어");
+/// ```
+pub fn truncate_to_width<'a>(
+    s: &'a str,
+    max_width: usize,
+    suffix: Option<&'a str>,
+) -> Truncated<'a> {
+    truncate_by(
+        s,
+        max_width,
+        suffix,
+        tables::str_width,
+        Profile::Unicode,
+        false,
+    )
+}
+
+/// Like [`truncate_to_width`], measuring with
+/// [`width_cjk`](crate::UnicodeWidthStr::width_cjk).
+#[cfg(feature = "cjk")]
+pub fn truncate_to_width_cjk<'a>(
+    s: &'a str,
+    max_width: usize,
+    suffix: Option<&'a str>,
+) -> Truncated<'a> {
+    truncate_by(
+        s,
+        max_width,
+        suffix,
+        tables::str_width_cjk,
+        Profile::Unicode,
+        false,
+    )
+}
+
+/// Like [`truncate_to_width`], skipping terminal escape sequences (see
+/// [`ansi_segments`]) in `s` and `suffix`.
+///
+/// Escape sequences in the part that is cut off are kept after the suffix,
+/// so a colour that is switched off at the end of `s` still is.
+///
+/// ```rust
+/// use unicode_width::truncate_to_width_ansi;
+///
+/// let line = "\x1b[31merror: disk full\x1b[0m";
+/// assert_eq!(
+///     truncate_to_width_ansi(line, 6, Some("…")).to_string(),
+///     "\x1b[31merror…\x1b[0m"
+/// );
+/// ```
+pub fn truncate_to_width_ansi<'a>(
+    s: &'a str,
+    max_width: usize,
+    suffix: Option<&'a str>,
+) -> Truncated<'a> {
+    truncate_by(
+        s,
+        max_width,
+        suffix,
+        tables::str_width,
+        Profile::Unicode,
+        true,
+    )
+}
+
+/// Like [`truncate_to_width_ansi`], measuring with
+/// [`width_cjk`](crate::UnicodeWidthStr::width_cjk).
+#[cfg(feature = "cjk")]
+pub fn truncate_to_width_ansi_cjk<'a>(
+    s: &'a str,
+    max_width: usize,
+    suffix: Option<&'a str>,
+) -> Truncated<'a> {
+    truncate_by(
+        s,
+        max_width,
+        suffix,
+        tables::str_width_cjk,
+        Profile::Unicode,
+        true,
+    )
+}
+
+/// Like [`truncate_to_width_ansi`], measuring as a terminal with `profile`
+/// does (see [`width_for`](crate::UnicodeWidthStr::width_for)).
+///
+/// ```rust
+/// use unicode_width::{truncate_to_width_ansi_for, Profile};
+///
+/// let line = "👍🏽 ok";
+/// assert_eq!(truncate_to_width_ansi_for(line, 4, None, Profile::Kitty).to_string(), "👍🏽 o");
+/// assert_eq!(truncate_to_width_ansi_for(line, 4, None, Profile::Xterm).to_string(), "👍🏽");
+/// ```
+pub fn truncate_to_width_ansi_for<'a>(
+    s: &'a str,
+    max_width: usize,
+    suffix: Option<&'a str>,
+    profile: Profile,
+) -> Truncated<'a> {
+    truncate_by(s, max_width, suffix, tables::str_width, profile, true)
+}
+
+/// Like [`truncate_to_width_ansi_for`], measuring with
+/// [`width_cjk_for`](crate::UnicodeWidthStr::width_cjk_for).
+#[cfg(feature = "cjk")]
+pub fn truncate_to_width_ansi_cjk_for<'a>(
+    s: &'a str,
+    max_width: usize,
+    suffix: Option<&'a str>,
+    profile: Profile,
+) -> Truncated<'a> {
+    truncate_by(s, max_width, suffix, tables::str_width_cjk, profile, true)
+}
+
+/// Pads `s` with spaces to at least `min_width` columns, placing it
+/// according to `align`. Strings that are already wide enough are left as
+/// they are.
+///
+/// ```rust
+/// use unicode_width::{pad_to_width, Align};
+///
+/// assert_eq!(pad_to_width("한", 5, Align::Left).to_string(), "한   ");
+/// assert_eq!(pad_to_width("한", 5, Align::Right).to_string(), "   한");
+/// assert_eq!(pad_to_width("한", 5, Align::Center).to_string(), " 한  ");
+/// ```
+pub fn pad_to_width(s: &str, min_width: usize, align: Align) -> Padded<'_> {
+    pad_by(s, min_width, align, tables::str_width)
+}
+
+/// Like [`pad_to_width`], measuring with
+/// [`width_cjk`](crate::UnicodeWidthStr::width_cjk).
+#[cfg(feature = "cjk")]
+pub fn pad_to_width_cjk(s: &str, min_width: usize, align: Align) -> Padded<'_> {
+    pad_by(s, min_width, align, tables::str_width_cjk)
+}
+
+/// Like [`pad_to_width`], not counting terminal escape sequences (see
+/// [`ansi_segments`]) towards the width of `s`.
+pub fn pad_to_width_ansi(s: &str, min_width: usize, align: Align) -> Padded<'_> {
+    pad_by(s, min_width, align, str_width_ansi)
+}
+
+/// Like [`pad_to_width_ansi`], measuring with
+/// [`width_cjk`](crate::UnicodeWidthStr::width_cjk).
+#[cfg(feature = "cjk")]
+pub fn pad_to_width_ansi_cjk(s: &str, min_width: usize, align: Align) -> Padded<'_> {
+    pad_by(s, min_width, align, str_width_ansi_cjk)
+}
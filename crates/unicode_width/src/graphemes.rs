@@ -0,0 +1,123 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{tables, Profile};
+
+/// Iterator over the clusters of a string, created by
+/// [`UnicodeWidthStr::width_indices`](crate::UnicodeWidthStr::width_indices).
+///
+/// Yields `(byte_offset, cluster, width)` for each cluster. A cluster is a
+/// user-perceived character: a base character together with the combining
+/// marks, variation selectors, emoji modifiers and zero width joiners that
+/// follow it, a regional indicator pair, a Hangul syllable written with
+/// conjoining jamo, `"\r\n"`, or a ligature from the
+/// [rules for determining width](crate#rules-for-determining-width).
+///
+/// Characters that change the width of their neighbours always end up in
+/// the same cluster, so the widths of the clusters add up to the width of
+/// the string.
+#[derive(Clone, Debug)]
+pub struct WidthIndices<'a> {
+    s: &'a str,
+    pos: usize,
+    width: fn(&str) -> usize,
+    profile: Profile,
+}
+
+impl<'a> WidthIndices<'a> {
+    pub(crate) fn new(s: &'a str, width: fn(&str) -> usize) -> Self {
+        Self::for_profile(s, width, Profile::Unicode)
+    }
+
+    /// Clusters of `s`, measured as a terminal with `profile` shows them.
+    pub(crate) fn for_profile(s: &'a str, width: fn(&str) -> usize, profile: Profile) -> Self {
+        WidthIndices {
+            s,
+            pos: 0,
+            width,
+            profile: profile.resolve(),
+        }
+    }
+}
+
+fn is_control(c: char) -> bool {
+    tables::single_char_width(c).is_none()
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1F1E6}'..='\u{1F1FF}')
+}
+
+fn is_hangul_leading_jamo(c: char) -> bool {
+    matches!(c, '\u{1100}'..='\u{115F}' | '\u{A960}'..='\u{A97C}')
+}
+
+impl<'a> Iterator for WidthIndices<'a> {
+    type Item = (usize, &'a str, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
+        let rest = &self.s[start..];
+        let mut chars = rest.char_indices();
+        let (_, first) = chars.next()?;
+        let mut end = first.len_utf8();
+        let mut prev = first;
+        // Regional indicators in a row, ending at `prev`
+        let mut regional = usize::from(is_regional_indicator(first));
+
+        for (i, c) in chars {
+            let next_end = i + c.len_utf8();
+            let joins = if prev == '\r' {
+                c == '\n'
+            } else if is_control(prev) || is_control(c) {
+                false
+            } else if prev == '\u{200D}' {
+                // Emoji ZWJ sequences, and conjuncts formed with ZWJ
+                true
+            } else if is_regional_indicator(c) && regional > 0 {
+                regional % 2 == 1
+            } else if tables::single_char_width(c) == Some(0) {
+                // Combining marks, variation selectors, ZWJ, Hangul V and T
+                true
+            } else if is_hangul_leading_jamo(prev) {
+                is_hangul_leading_jamo(c) || matches!(c, '\u{AC00}'..='\u{D7A3}')
+            } else if c == '\u{2D7F}' {
+                // TIFINAGH CONSONANT JOINER
+                matches!(prev, '\u{2D31}'..='\u{2D65}' | '\u{2D6F}')
+            } else if prev.is_ascii() && c.is_ascii() {
+                false
+            } else {
+                // Ligatures: `c` changes the width of the cluster so far.
+                let width = self.width;
+                width(&rest[..next_end]) != width(&rest[..end]) + width(&rest[i..next_end])
+            };
+            if !joins {
+                break;
+            }
+            regional = if is_regional_indicator(c) {
+                regional + 1
+            } else {
+                0
+            };
+            end = next_end;
+            prev = c;
+        }
+
+        self.pos = start + end;
+        let cluster = &rest[..end];
+        Some((
+            start,
+            cluster,
+            self.profile.cluster_width(cluster, self.width),
+        ))
+    }
+}
+
+impl core::iter::FusedIterator for WidthIndices<'_> {}
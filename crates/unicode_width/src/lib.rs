@@ -182,8 +182,35 @@
 )]
 #![no_std]
 
-pub use tables::UNICODE_VERSION;
+#[cfg(feature = "std")]
+extern crate std;
 
+pub use ansi::{ansi_segments, AnsiSegment, AnsiSegments};
+#[cfg(feature = "cjk")]
+pub use bytes::width_bytes_cjk;
+pub use bytes::{width_bytes, InvalidWidth};
+pub use fit::{
+    pad_to_width, pad_to_width_ansi, truncate_to_width, truncate_to_width_ansi,
+    truncate_to_width_ansi_for, Align, Padded, Truncated,
+};
+#[cfg(feature = "cjk")]
+pub use fit::{
+    pad_to_width_ansi_cjk, pad_to_width_cjk, truncate_to_width_ansi_cjk,
+    truncate_to_width_ansi_cjk_for, truncate_to_width_cjk,
+};
+pub use graphemes::WidthIndices;
+pub use options::{AmbiguousWidth, ControlWidth, WidthOptions};
+pub use profile::Profile;
+pub use tables::{single_char_width, str_width, UNICODE_VERSION};
+#[cfg(feature = "cjk")]
+pub use tables::{single_char_width_cjk, str_width_cjk};
+
+mod ansi;
+mod bytes;
+mod fit;
+mod graphemes;
+mod options;
+mod profile;
 mod tables;
 
 mod private {
@@ -216,6 +243,25 @@ pub trait UnicodeWidthChar: private::Sealed {
     /// CJK contexts.
     #[cfg(feature = "cjk")]
     fn width_cjk(self) -> Option<usize>;
+
+    /// Returns the character's displayed width in columns according to
+    /// `options`, or `None` if it is a control character that `options`
+    /// leaves unmeasured.
+    ///
+    /// A tab is measured as if it started at a tab stop.
+    ///
+    /// ```rust
+    /// use unicode_width::{ControlWidth, UnicodeWidthChar, WidthOptions};
+    ///
+    /// let caret = WidthOptions {
+    ///     control: ControlWidth::Caret,
+    ///     ..WidthOptions::new()
+    /// };
+    /// assert_eq!('\x1b'.width_with(&caret), Some(2));
+    /// assert_eq!('\x1b'.width_with(&WidthOptions::new()), None);
+    /// assert_eq!('\t'.width_with(&WidthOptions::new()), Some(8));
+    /// ```
+    fn width_with(self, options: &WidthOptions) -> Option<usize>;
 }
 
 impl UnicodeWidthChar for char {
@@ -229,6 +275,11 @@ impl UnicodeWidthChar for char {
     fn width_cjk(self) -> Option<usize> {
         tables::single_char_width_cjk(self)
     }
+
+    #[inline]
+    fn width_with(self, options: &WidthOptions) -> Option<usize> {
+        options.char_width(self)
+    }
 }
 
 /// Methods for determining displayed width of Unicode strings.
@@ -249,6 +300,67 @@ pub trait UnicodeWidthStr: private::Sealed {
     /// CJK contexts.
     #[cfg(feature = "cjk")]
     fn width_cjk(&self) -> usize;
+
+    /// Returns an iterator over the clusters of the string, yielding the
+    /// byte offset, text and width of each, see [`WidthIndices`].
+    ///
+    /// Cut the string only at these offsets to truncate or wrap it without
+    /// splitting an emoji or a Hangul syllable.
+    ///
+    /// ```rust
+    /// use unicode_width::UnicodeWidthStr;
+    ///
+    /// let clusters: Vec<_> = "a👩‍🔬한".width_indices().collect();
+    /// assert_eq!(clusters, [(0, "a", 1), (1, "👩‍🔬", 2), (12, "한", 2)]);
+    /// ```
+    fn width_indices(&self) -> WidthIndices<'_>;
+
+    /// Like [`width_indices`](UnicodeWidthStr::width_indices), with the
+    /// widths of [`width_cjk`](UnicodeWidthStr::width_cjk).
+    #[cfg(feature = "cjk")]
+    fn width_indices_cjk(&self) -> WidthIndices<'_>;
+
+    /// Returns the string's displayed width in columns, not counting
+    /// terminal escape sequences such as colour codes and hyperlinks (see
+    /// [`ansi_segments`]).
+    ///
+    /// ```rust
+    /// use unicode_width::UnicodeWidthStr;
+    ///
+    /// assert_eq!("\x1b[32mok\x1b[0m".width_ansi(), 2);
+    /// assert_eq!("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\".width_ansi(), 4);
+    /// ```
+    fn width_ansi(&self) -> usize;
+
+    /// Like [`width_ansi`](UnicodeWidthStr::width_ansi), measuring with
+    /// [`width_cjk`](UnicodeWidthStr::width_cjk).
+    #[cfg(feature = "cjk")]
+    fn width_ansi_cjk(&self) -> usize;
+
+    /// Returns the string's displayed width in columns in a terminal with
+    /// `profile`, which decides how emoji sequences are laid out (see
+    /// [`Profile`]).
+    ///
+    /// ```rust
+    /// use unicode_width::{Profile, UnicodeWidthStr};
+    ///
+    /// assert_eq!("👩‍🔬".width_for(Profile::Kitty), 2);
+    /// assert_eq!("👩‍🔬".width_for(Profile::Xterm), 4);
+    /// ```
+    fn width_for(&self, profile: Profile) -> usize;
+
+    /// Like [`width_for`](UnicodeWidthStr::width_for), measuring with
+    /// [`width_cjk`](UnicodeWidthStr::width_cjk).
+    #[cfg(feature = "cjk")]
+    fn width_cjk_for(&self, profile: Profile) -> usize;
+
+    /// Returns the string's displayed width in columns according to
+    /// `options`, starting at a tab stop.
+    ///
+    /// Text between tabs and measured control characters is measured as by
+    /// [`width`](UnicodeWidthStr::width) or
+    /// [`width_cjk`](UnicodeWidthStr::width_cjk).
+    fn width_with(&self, options: &WidthOptions) -> usize;
 }
 
 impl UnicodeWidthStr for str {
@@ -262,4 +374,42 @@ impl UnicodeWidthStr for str {
     fn width_cjk(&self) -> usize {
         tables::str_width_cjk(self)
     }
+
+    #[inline]
+    fn width_indices(&self) -> WidthIndices<'_> {
+        WidthIndices::new(self, tables::str_width)
+    }
+
+    #[cfg(feature = "cjk")]
+    #[inline]
+    fn width_indices_cjk(&self) -> WidthIndices<'_> {
+        WidthIndices::new(self, tables::str_width_cjk)
+    }
+
+    #[inline]
+    fn width_ansi(&self) -> usize {
+        ansi::str_width_ansi(self)
+    }
+
+    #[cfg(feature = "cjk")]
+    #[inline]
+    fn width_ansi_cjk(&self) -> usize {
+        ansi::str_width_ansi_cjk(self)
+    }
+
+    #[inline]
+    fn width_for(&self, profile: Profile) -> usize {
+        profile::str_width_for(self, profile, tables::str_width)
+    }
+
+    #[cfg(feature = "cjk")]
+    #[inline]
+    fn width_cjk_for(&self, profile: Profile) -> usize {
+        profile::str_width_for(self, profile, tables::str_width_cjk)
+    }
+
+    #[inline]
+    fn width_with(&self, options: &WidthOptions) -> usize {
+        options.str_width_with(self)
+    }
 }
@@ -0,0 +1,200 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::tables;
+
+/// How characters in the Ambiguous category of
+/// [Unicode Standard Annex #11](http://www.unicode.org/reports/tr11/) are
+/// measured.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AmbiguousWidth {
+    /// 1 column wide, as in [`width`](crate::UnicodeWidthStr::width).
+    #[default]
+    Narrow,
+    /// 2 columns wide, as in `width_cjk`. The variant is there with or
+    /// without the `cjk` feature, but without it the tables for East Asian
+    /// widths are not built, and `Wide` measures as `Narrow`.
+    Wide,
+    /// `Wide` in Chinese, Japanese and Korean locales and `Narrow` in
+    /// others, see [`resolve`](AmbiguousWidth::resolve).
+    Auto,
+}
+
+impl AmbiguousWidth {
+    /// The setting for the POSIX locale `name`, such as `ko_KR.UTF-8`:
+    /// `Wide` if its language is Chinese, Japanese or Korean, otherwise
+    /// `Narrow`.
+    ///
+    /// ```rust
+    /// use unicode_width::AmbiguousWidth;
+    ///
+    /// assert_eq!(AmbiguousWidth::from_locale("C.UTF-8"), AmbiguousWidth::Narrow);
+    /// assert_eq!(AmbiguousWidth::from_locale("ja_JP.eucJP"), AmbiguousWidth::Wide);
+    /// ```
+    pub fn from_locale(name: &str) -> AmbiguousWidth {
+        let lang = name.split(['_', '.', '@']).next().unwrap_or("");
+        match lang {
+            "ja" | "ko" | "zh" => AmbiguousWidth::Wide,
+            _ => AmbiguousWidth::Narrow,
+        }
+    }
+
+    /// Returns `Narrow` or `Wide`, deciding `Auto` from the first of the
+    /// `LC_ALL`, `LC_CTYPE` and `LANG` environment variables that is set.
+    /// The environment is read once, the first time it is needed.
+    ///
+    /// Without the `std` feature the environment is not available, and
+    /// `Auto` is `Narrow`.
+    pub fn resolve(self) -> AmbiguousWidth {
+        match self {
+            AmbiguousWidth::Auto => from_env(),
+            width => width,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn from_env() -> AmbiguousWidth {
+    use std::{env, sync::OnceLock};
+
+    static FROM_ENV: OnceLock<AmbiguousWidth> = OnceLock::new();
+    *FROM_ENV.get_or_init(|| {
+        ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map_or(AmbiguousWidth::Narrow, |value| {
+                AmbiguousWidth::from_locale(&value)
+            })
+    })
+}
+
+#[cfg(not(feature = "std"))]
+fn from_env() -> AmbiguousWidth {
+    AmbiguousWidth::Narrow
+}
+
+/// How control characters (`U+0000` to `U+001F` and `U+007F` to `U+009F`)
+/// are measured.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlWidth {
+    /// As [`width`](crate::UnicodeWidthStr::width) measures them: `None`
+    /// for a single character, and the width from the tables within a string.
+    #[default]
+    Unicode,
+    /// Shown in caret notation: `^@` to `^_` and `^?` for `DEL`, 2 columns
+    /// wide. C1 controls are shown as the equivalent 7-bit escape, such as
+    /// `^[E` for `NEXT LINE`, 3 columns wide.
+    Caret,
+    /// Shown as a single replacement glyph, such as the Control Pictures
+    /// `␀` to `␟` and `␡`, 1 column wide.
+    Replacement,
+}
+
+/// Settings for [`UnicodeWidthChar::width_with`](crate::UnicodeWidthChar::width_with)
+/// and [`UnicodeWidthStr::width_with`](crate::UnicodeWidthStr::width_with).
+///
+/// With the default settings, only tabs are measured differently from
+/// [`width`](crate::UnicodeWidthStr::width).
+///
+/// ```rust
+/// use unicode_width::{ControlWidth, UnicodeWidthStr, WidthOptions};
+///
+/// let options = WidthOptions {
+///     tab_size: 4,
+///     control: ControlWidth::Caret,
+///     ..WidthOptions::default()
+/// };
+/// assert_eq!("a\tb\x07".width_with(&options), 7);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WidthOptions {
+    /// Distance between tab stops. A tab advances to the next stop, so it
+    /// is between 1 and `tab_size` columns wide. `0` measures tabs like
+    /// other control characters.
+    pub tab_size: usize,
+    /// How control characters other than tabs are measured.
+    pub control: ControlWidth,
+    /// How ambiguous width characters are measured.
+    pub ambiguous: AmbiguousWidth,
+}
+
+impl WidthOptions {
+    /// The default settings: tab stops every 8 columns, with other control
+    /// characters and ambiguous width characters measured as by
+    /// [`width`](crate::UnicodeWidthStr::width).
+    pub const fn new() -> Self {
+        WidthOptions {
+            tab_size: 8,
+            control: ControlWidth::Unicode,
+            ambiguous: AmbiguousWidth::Narrow,
+        }
+    }
+
+    fn str_width(&self) -> fn(&str) -> usize {
+        match self.ambiguous.resolve() {
+            #[cfg(feature = "cjk")]
+            AmbiguousWidth::Wide => tables::str_width_cjk,
+            _ => tables::str_width,
+        }
+    }
+
+    fn is_tab(&self, c: char) -> bool {
+        c == '\t' && self.tab_size > 0
+    }
+
+    fn control_width(&self, c: char) -> Option<usize> {
+        match self.control {
+            ControlWidth::Unicode => None,
+            ControlWidth::Caret if c < '\u{80}' => Some(2),
+            ControlWidth::Caret => Some(3),
+            ControlWidth::Replacement => Some(1),
+        }
+    }
+
+    pub(crate) fn char_width(&self, c: char) -> Option<usize> {
+        if self.is_tab(c) {
+            return Some(self.tab_size);
+        }
+        if c.is_control() {
+            return self.control_width(c);
+        }
+        match self.ambiguous.resolve() {
+            #[cfg(feature = "cjk")]
+            AmbiguousWidth::Wide => tables::single_char_width_cjk(c),
+            _ => tables::single_char_width(c),
+        }
+    }
+
+    pub(crate) fn str_width_with(&self, s: &str) -> usize {
+        let width = self.str_width();
+        let splits =
+            |c: char| self.is_tab(c) || c.is_control() && self.control != ControlWidth::Unicode;
+        let mut column = 0;
+        let mut rest = s;
+        while let Some(i) = rest.find(splits) {
+            column += width(&rest[..i]);
+            let c = rest[i..].chars().next().unwrap_or_default();
+            column += if self.is_tab(c) {
+                self.tab_size - column % self.tab_size
+            } else {
+                self.control_width(c).unwrap_or(0)
+            };
+            rest = &rest[i + c.len_utf8()..];
+        }
+        column + width(rest)
+    }
+}
+
+impl Default for WidthOptions {
+    fn default() -> Self {
+        WidthOptions::new()
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright 2012-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{tables, WidthIndices};
+
+/// How a terminal emulator lays out emoji sequences, for
+/// [`width_for`](crate::UnicodeWidthStr::width_for).
+///
+/// Terminals agree on the width of single characters, but not on emoji
+/// sequences. Most follow [Unicode Technical Standard #51] as
+/// [`width`](crate::UnicodeWidthStr::width) does, others give every
+/// character of a sequence its own cells:
+///
+/// | Sequence                         | `Unicode`, `Kitty`, `WezTerm`, `WindowsTerminal` | `Xterm`, `Vte` |
+/// |----------------------------------|--------------------------------------------------|----------------|
+/// | `"❤\u{FE0F}"` (VS16)             | 2                                                | 1              |
+/// | `"⌚\u{FE0E}"` (VS15)             | 1                                                | 2              |
+/// | `"👍🏽"` (emoji modifier)          | 2                                                | 4              |
+/// | `"👩\u{200D}🔬"` (ZWJ sequence)  | 2                                                | 4              |
+/// | `"🇰🇷"` (flag)                     | 2                                                | 2              |
+///
+/// [Unicode Technical Standard #51]: https://www.unicode.org/reports/tr51/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Profile {
+    /// The rules of [`width`](crate::UnicodeWidthStr::width), followed by
+    /// most terminals.
+    #[default]
+    Unicode,
+    /// xterm, and terminals that measure with the C library's `wcwidth`.
+    Xterm,
+    /// Terminals built on VTE, such as GNOME Terminal and Terminator.
+    Vte,
+    /// kitty.
+    Kitty,
+    /// WezTerm.
+    WezTerm,
+    /// Windows Terminal.
+    WindowsTerminal,
+    /// The terminal the program is running in, see
+    /// [`resolve`](Profile::resolve).
+    Auto,
+}
+
+impl Profile {
+    /// The profile for a terminal whose environment variables are looked
+    /// up with `var`. kitty sets `TERM` to `xterm-kitty`, WezTerm sets
+    /// `TERM_PROGRAM` to `WezTerm`, Windows Terminal sets `WT_SESSION`, VTE
+    /// sets `VTE_VERSION` and xterm sets `XTERM_VERSION`. Any other `TERM`
+    /// is no help, as most terminals set it to an xterm variant, and
+    /// terminals that aren't recognised get `Unicode`.
+    ///
+    /// ```rust
+    /// use unicode_width::Profile;
+    ///
+    /// let env = |vars: &'static [(&str, &str)]| {
+    ///     move |name: &str| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| *value)
+    /// };
+    /// assert_eq!(Profile::from_vars(env(&[("TERM", "xterm-kitty")])), Profile::Kitty);
+    /// assert_eq!(Profile::from_vars(env(&[("XTERM_VERSION", "XTerm(390)")])), Profile::Xterm);
+    /// assert_eq!(Profile::from_vars(env(&[("TERM", "xterm-256color")])), Profile::Unicode);
+    /// ```
+    pub fn from_vars<'a>(var: impl Fn(&str) -> Option<&'a str>) -> Profile {
+        // TERM and TERM_PROGRAM are set by the terminal itself, the others
+        // may be inherited from a terminal the program was started from.
+        if var("TERM") == Some("xterm-kitty") {
+            Profile::Kitty
+        } else if var("TERM_PROGRAM") == Some("WezTerm") || var("TERM") == Some("wezterm") {
+            Profile::WezTerm
+        } else if var("WT_SESSION").is_some() {
+            Profile::WindowsTerminal
+        } else if var("VTE_VERSION").is_some() {
+            Profile::Vte
+        } else if var("XTERM_VERSION").is_some() {
+            Profile::Xterm
+        } else {
+            Profile::Unicode
+        }
+    }
+
+    /// Returns a profile other than `Auto`, deciding `Auto` from the
+    /// environment with [`from_vars`](Profile::from_vars). The environment
+    /// is read once, the first time it is needed.
+    ///
+    /// Without the `std` feature the environment is not available, and
+    /// `Auto` is `Unicode`.
+    pub fn resolve(self) -> Profile {
+        match self {
+            Profile::Auto => from_env(),
+            profile => profile,
+        }
+    }
+
+    /// Whether the terminal lays out emoji sequences as
+    /// [`width`](crate::UnicodeWidthStr::width) measures them.
+    fn joins_emoji(self) -> bool {
+        !matches!(self, Profile::Xterm | Profile::Vte)
+    }
+
+    /// The width of `cluster`, one item of [`WidthIndices`], whose width by
+    /// the Unicode rules is `width(cluster)`.
+    pub(crate) fn cluster_width(self, cluster: &str, width: fn(&str) -> usize) -> usize {
+        let starts_emoji_seq = |c| {
+            tables::starts_emoji_presentation_seq(c)
+                || tables::starts_non_ideographic_text_presentation_seq(c)
+                || tables::is_emoji_modifier_base(c)
+        };
+        match cluster.chars().next() {
+            Some(c) if !self.joins_emoji() && starts_emoji_seq(c) => {
+                // Each character on its own: variation selectors and ZWJ
+                // are ignored, modifiers take their own two columns.
+                let mut buf = [0; 4];
+                cluster
+                    .chars()
+                    .map(|c| width(c.encode_utf8(&mut buf)))
+                    .sum()
+            }
+            _ => width(cluster),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn from_env() -> Profile {
+    use std::{env, sync::OnceLock};
+
+    const VARS: [&str; 5] = [
+        "TERM",
+        "TERM_PROGRAM",
+        "WT_SESSION",
+        "VTE_VERSION",
+        "XTERM_VERSION",
+    ];
+    static FROM_ENV: OnceLock<Profile> = OnceLock::new();
+    *FROM_ENV.get_or_init(|| {
+        let values = VARS.map(|name| env::var(name).ok());
+        Profile::from_vars(|name| {
+            let i = VARS.iter().position(|&var| var == name)?;
+            values[i].as_deref()
+        })
+    })
+}
+
+#[cfg(not(feature = "std"))]
+fn from_env() -> Profile {
+    Profile::Unicode
+}
+
+/// The width of `s` in a terminal with `profile`, `width` being the
+/// Unicode rules to start from.
+pub(crate) fn str_width_for(s: &str, profile: Profile, width: fn(&str) -> usize) -> usize {
+    let profile = profile.resolve();
+    if profile.joins_emoji() {
+        return width(s);
+    }
+    WidthIndices::for_profile(s, width, profile)
+        .map(|(_, _, w)| w)
+        .sum()
+}
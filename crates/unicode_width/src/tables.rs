@@ -10,8 +10,6 @@
 
 // NOTE: The following code was generated by "scripts/unicode.py", do not edit directly
 
-use core::cmp::Ordering;
-
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 struct WidthInfo(u16);
 
@@ -62,27 +60,27 @@ impl WidthInfo {
 
     /// Whether this width mode is ligature_transparent
     /// (has 5th MSB set.)
-    fn is_ligature_transparent(self) -> bool {
+    const fn is_ligature_transparent(self) -> bool {
         (self.0 & 0b0000_1000_0000_0000) == 0b0000_1000_0000_0000
     }
 
     /// Sets 6th MSB.
-    fn set_zwj_bit(self) -> Self {
+    const fn set_zwj_bit(self) -> Self {
         Self(self.0 | 0b0000_0100_0000_0000)
     }
 
     /// Has top bit set
-    fn is_emoji_presentation(self) -> bool {
+    const fn is_emoji_presentation(self) -> bool {
         (self.0 & 0b1000_0000_0000_0000) == 0b1000_0000_0000_0000
     }
 
     /// Has top bit set
-    fn is_zwj_emoji_presentation(self) -> bool {
+    const fn is_zwj_emoji_presentation(self) -> bool {
         (self.0 & 0b1011_0000_0000_0000) == 0b1001_0000_0000_0000
     }
 
     /// Set top bit
-    fn set_emoji_presentation(self) -> Self {
+    const fn set_emoji_presentation(self) -> Self {
         if (self.0 & 0b0010_0000_0000_0000) == 0b0010_0000_0000_0000
             || (self.0 & 0b1001_0000_0000_0000) == 0b0001_0000_0000_0000
         {
@@ -93,7 +91,7 @@ impl WidthInfo {
     }
 
     /// Clear top bit
-    fn unset_emoji_presentation(self) -> Self {
+    const fn unset_emoji_presentation(self) -> Self {
         if (self.0 & 0b0010_0000_0000_0000) == 0b0010_0000_0000_0000 {
             Self(self.0 & 0b0111_1111_1111_1111)
         } else {
@@ -102,12 +100,12 @@ impl WidthInfo {
     }
 
     /// Has 2nd bit set
-    fn is_text_presentation(self) -> bool {
+    const fn is_text_presentation(self) -> bool {
         (self.0 & 0b0100_0000_0000_0000) == 0b0100_0000_0000_0000
     }
 
     /// Set 2nd bit
-    fn set_text_presentation(self) -> Self {
+    const fn set_text_presentation(self) -> Self {
         if (self.0 & 0b0010_0000_0000_0000) == 0b0010_0000_0000_0000 {
             Self(self.0 | 0b0100_0000_0000_0000)
         } else {
@@ -116,7 +114,7 @@ impl WidthInfo {
     }
 
     /// Clear 2nd bit
-    fn unset_text_presentation(self) -> Self {
+    const fn unset_text_presentation(self) -> Self {
         Self(self.0 & 0b1011_1111_1111_1111)
     }
 }
@@ -130,11 +128,11 @@ pub const UNICODE_VERSION: (u8, u8, u8) = (15, 1, 0);
 ///
 /// # Maintenance
 /// The tables themselves are autogenerated but this function is hardcoded. You should have
-/// nothing to worry about if you re-run `unicode.py` (for example, when updating Unicode.)
+/// nothing to worry about if you re-run `unicode_width_gen` (for example, when updating Unicode.)
 /// However, if you change the *actual structure* of the lookup tables (perhaps by editing the
-/// `make_tables` function in `unicode.py`) you must ensure that this code reflects those changes.
+/// `pack` function in `unicode_width_gen`) you must ensure that this code reflects those changes.
 #[inline]
-fn lookup_width(c: char) -> (u8, WidthInfo) {
+const fn lookup_width(c: char) -> (u8, WidthInfo) {
     let cp = c as usize;
 
     let t1_offset = WIDTH_ROOT.0[cp >> 13];
@@ -142,13 +140,13 @@ fn lookup_width(c: char) -> (u8, WidthInfo) {
     // Each sub-table in WIDTH_MIDDLE is 7 bits, and each stored entry is a byte,
     // so each sub-table is 128 bytes in size.
     // (Sub-tables are selected using the computed offset from the previous table.)
-    let t2_offset = WIDTH_MIDDLE.0[usize::from(t1_offset)][cp >> 7 & 0x3F];
+    let t2_offset = WIDTH_MIDDLE.0[t1_offset as usize][cp >> 7 & 0x3F];
 
     // Each sub-table in WIDTH_LEAVES is 6 bits, but each stored entry is 2 bits.
     // This is accomplished by packing four stored entries into one byte.
     // So each sub-table is 2**(7-2) == 32 bytes in size.
     // Since this is the last table, each entry represents an encoded width.
-    let packed_widths = WIDTH_LEAVES.0[usize::from(t2_offset)][cp >> 2 & 0x1F];
+    let packed_widths = WIDTH_LEAVES.0[t2_offset as usize][cp >> 2 & 0x1F];
 
     // Extract the packed width
     let width = packed_widths >> (2 * (cp & 0b11)) & 0b11;
@@ -179,7 +177,7 @@ fn lookup_width(c: char) -> (u8, WidthInfo) {
 /// `None` if `c` is a control character.
 /// Ambiguous width characters are treated as narrow.
 #[inline]
-pub fn single_char_width(c: char) -> Option<usize> {
+pub const fn single_char_width(c: char) -> Option<usize> {
     if c < '\u{7F}' {
         if c >= '\u{20}' {
             // U+0020 to U+007F (exclusive) are single-width ASCII codepoints
@@ -190,7 +188,7 @@ pub fn single_char_width(c: char) -> Option<usize> {
         }
     } else if c >= '\u{A0}' {
         // No characters >= U+00A0 are control codes, so we can consult the lookup tables
-        Some(lookup_width(c).0.into())
+        Some(lookup_width(c).0 as usize)
     } else {
         // U+007F to U+00A0 (exclusive) are control codes
         None
@@ -200,7 +198,7 @@ pub fn single_char_width(c: char) -> Option<usize> {
 /// Returns the [UAX #11](https://www.unicode.org/reports/tr11/) based width of `c`.
 /// Ambiguous width characters are treated as narrow.
 #[inline]
-fn width_in_str(c: char, mut next_info: WidthInfo) -> (i8, WidthInfo) {
+const fn width_in_str(c: char, mut next_info: WidthInfo) -> (i8, WidthInfo) {
     if next_info.is_emoji_presentation() {
         if starts_emoji_presentation_seq(c) {
             let width = if next_info.is_zwj_emoji_presentation() {
@@ -216,12 +214,12 @@ fn width_in_str(c: char, mut next_info: WidthInfo) -> (i8, WidthInfo) {
     if c <= '\u{A0}' {
         match c {
             '\n' => (1, WidthInfo::LINE_FEED),
-            '\r' if next_info == WidthInfo::LINE_FEED => (0, WidthInfo::DEFAULT),
+            '\r' if next_info.0 == WidthInfo::LINE_FEED.0 => (0, WidthInfo::DEFAULT),
             _ => (1, WidthInfo::DEFAULT),
         }
     } else {
         // Fast path
-        if next_info != WidthInfo::DEFAULT {
+        if next_info.0 != WidthInfo::DEFAULT.0 {
             if c == '\u{FE0F}' {
                 return (0, next_info.set_emoji_presentation());
             }
@@ -379,7 +377,7 @@ fn width_in_str(c: char, mut next_info: WidthInfo) -> (i8, WidthInfo) {
                     '\u{1F3F4}',
                 ) => return (0, WidthInfo::EMOJI_PRESENTATION),
                 (WidthInfo::ZWJ_EMOJI_PRESENTATION, _)
-                    if lookup_width(c).1 == WidthInfo::EMOJI_PRESENTATION =>
+                    if matches!(lookup_width(c).1, WidthInfo::EMOJI_PRESENTATION) =>
                 {
                     return (0, WidthInfo::EMOJI_PRESENTATION)
                 }
@@ -394,17 +392,87 @@ fn width_in_str(c: char, mut next_info: WidthInfo) -> (i8, WidthInfo) {
     }
 }
 
+/// Whether `s` is all printable ASCII, `' '` to `'~'`, each byte of which
+/// is one column wide.
+///
+/// The bytes are checked 16 at a time without branching, which the compiler
+/// turns into SIMD instructions; long log lines are mostly this case.
 #[inline]
-pub fn str_width(s: &str) -> usize {
-    s.chars()
-        .rfold(
-            (0, WidthInfo::DEFAULT),
-            |(sum, next_info), c| -> (usize, WidthInfo) {
-                let (add, info) = width_in_str(c, next_info);
-                (sum.wrapping_add_signed(isize::from(add)), info)
-            },
-        )
-        .0
+pub const fn is_printable_ascii(s: &str) -> bool {
+    let mut rest = s.as_bytes();
+    while let Some((chunk, tail)) = rest.split_first_chunk::<16>() {
+        let mut printable = true;
+        let mut i = 0;
+        while i < chunk.len() {
+            printable &= chunk[i].wrapping_sub(b' ') < 0x5F;
+            i += 1;
+        }
+        if !printable {
+            return false;
+        }
+        rest = tail;
+    }
+    while let [b, tail @ ..] = rest {
+        if b.wrapping_sub(b' ') >= 0x5F {
+            return false;
+        }
+        rest = tail;
+    }
+    true
+}
+
+/// Splits the last character off `bytes`, which must be valid UTF-8.
+#[inline]
+const fn split_last_char(bytes: &[u8]) -> Option<(char, &[u8])> {
+    const fn cont(b: u8) -> u32 {
+        (b & 0x3F) as u32
+    }
+    let (cp, rest) = match bytes {
+        [] => return None,
+        [rest @ .., b0] if *b0 < 0x80 => (*b0 as u32, rest),
+        [rest @ .., b0, b1] if *b0 >= 0xC0 && *b0 < 0xE0 => {
+            ((*b0 as u32 & 0x1F) << 6 | cont(*b1), rest)
+        }
+        [rest @ .., b0, b1, b2] if *b0 >= 0xE0 && *b0 < 0xF0 => {
+            ((*b0 as u32 & 0x0F) << 12 | cont(*b1) << 6 | cont(*b2), rest)
+        }
+        [rest @ .., b0, b1, b2, b3] => (
+            (*b0 as u32 & 0x07) << 18 | cont(*b1) << 12 | cont(*b2) << 6 | cont(*b3),
+            rest,
+        ),
+        _ => return None,
+    };
+    match char::from_u32(cp) {
+        Some(c) => Some((c, rest)),
+        None => None,
+    }
+}
+
+/// Returns the displayed width of `s` in columns, as
+/// [`width`](crate::UnicodeWidthStr::width) does.
+///
+/// As a `const fn`, it measures static strings at compile time:
+///
+/// ```rust
+/// const HEADER: &str = "이름  상태";
+/// const _: () = assert!(unicode_width::str_width(HEADER) == 10);
+/// ```
+#[inline]
+pub const fn str_width(s: &str) -> usize {
+    if is_printable_ascii(s) {
+        return s.len();
+    }
+    // Characters are measured from the end, each depending on those after it
+    let mut sum: usize = 0;
+    let mut next_info = WidthInfo::DEFAULT;
+    let mut rest = s.as_bytes();
+    while let Some((c, init)) = split_last_char(rest) {
+        let (add, info) = width_in_str(c, next_info);
+        sum = sum.wrapping_add_signed(add as isize);
+        next_info = info;
+        rest = init;
+    }
+    sum
 }
 
 /// Returns the [UAX #11](https://www.unicode.org/reports/tr11/) based width of `c` by
@@ -412,12 +480,12 @@ pub fn str_width(s: &str) -> usize {
 ///
 /// # Maintenance
 /// The tables themselves are autogenerated but this function is hardcoded. You should have
-/// nothing to worry about if you re-run `unicode.py` (for example, when updating Unicode.)
+/// nothing to worry about if you re-run `unicode_width_gen` (for example, when updating Unicode.)
 /// However, if you change the *actual structure* of the lookup tables (perhaps by editing the
-/// `make_tables` function in `unicode.py`) you must ensure that this code reflects those changes.
+/// `pack` function in `unicode_width_gen`) you must ensure that this code reflects those changes.
 #[cfg(feature = "cjk")]
 #[inline]
-fn lookup_width_cjk(c: char) -> (u8, WidthInfo) {
+const fn lookup_width_cjk(c: char) -> (u8, WidthInfo) {
     let cp = c as usize;
 
     let t1_offset = WIDTH_ROOT_CJK.0[cp >> 13];
@@ -425,13 +493,13 @@ fn lookup_width_cjk(c: char) -> (u8, WidthInfo) {
     // Each sub-table in WIDTH_MIDDLE is 7 bits, and each stored entry is a byte,
     // so each sub-table is 128 bytes in size.
     // (Sub-tables are selected using the computed offset from the previous table.)
-    let t2_offset = WIDTH_MIDDLE.0[usize::from(t1_offset)][cp >> 7 & 0x3F];
+    let t2_offset = WIDTH_MIDDLE.0[t1_offset as usize][cp >> 7 & 0x3F];
 
     // Each sub-table in WIDTH_LEAVES is 6 bits, but each stored entry is 2 bits.
     // This is accomplished by packing four stored entries into one byte.
     // So each sub-table is 2**(7-2) == 32 bytes in size.
     // Since this is the last table, each entry represents an encoded width.
-    let packed_widths = WIDTH_LEAVES.0[usize::from(t2_offset)][cp >> 2 & 0x1F];
+    let packed_widths = WIDTH_LEAVES.0[t2_offset as usize][cp >> 2 & 0x1F];
 
     // Extract the packed width
     let width = packed_widths >> (2 * (cp & 0b11)) & 0b11;
@@ -463,7 +531,7 @@ fn lookup_width_cjk(c: char) -> (u8, WidthInfo) {
 /// Ambiguous width characters are treated as wide.
 #[cfg(feature = "cjk")]
 #[inline]
-pub fn single_char_width_cjk(c: char) -> Option<usize> {
+pub const fn single_char_width_cjk(c: char) -> Option<usize> {
     if c < '\u{7F}' {
         if c >= '\u{20}' {
             // U+0020 to U+007F (exclusive) are single-width ASCII codepoints
@@ -474,7 +542,7 @@ pub fn single_char_width_cjk(c: char) -> Option<usize> {
         }
     } else if c >= '\u{A0}' {
         // No characters >= U+00A0 are control codes, so we can consult the lookup tables
-        Some(lookup_width_cjk(c).0.into())
+        Some(lookup_width_cjk(c).0 as usize)
     } else {
         // U+007F to U+00A0 (exclusive) are control codes
         None
@@ -485,7 +553,7 @@ pub fn single_char_width_cjk(c: char) -> Option<usize> {
 /// Ambiguous width characters are treated as wide.
 #[cfg(feature = "cjk")]
 #[inline]
-fn width_in_str_cjk(c: char, mut next_info: WidthInfo) -> (i8, WidthInfo) {
+const fn width_in_str_cjk(c: char, mut next_info: WidthInfo) -> (i8, WidthInfo) {
     if next_info.is_emoji_presentation() {
         if starts_emoji_presentation_seq(c) {
             let width = if next_info.is_zwj_emoji_presentation() {
@@ -508,12 +576,12 @@ fn width_in_str_cjk(c: char, mut next_info: WidthInfo) -> (i8, WidthInfo) {
     if c <= '\u{A0}' {
         match c {
             '\n' => (1, WidthInfo::LINE_FEED),
-            '\r' if next_info == WidthInfo::LINE_FEED => (0, WidthInfo::DEFAULT),
+            '\r' if next_info.0 == WidthInfo::LINE_FEED.0 => (0, WidthInfo::DEFAULT),
             _ => (1, WidthInfo::DEFAULT),
         }
     } else {
         // Fast path
-        if next_info != WidthInfo::DEFAULT {
+        if next_info.0 != WidthInfo::DEFAULT.0 {
             if c == '\u{FE0F}' {
                 return (0, next_info.set_emoji_presentation());
             }
@@ -670,7 +738,7 @@ fn width_in_str_cjk(c: char, mut next_info: WidthInfo) -> (i8, WidthInfo) {
                     '\u{1F3F4}',
                 ) => return (0, WidthInfo::EMOJI_PRESENTATION),
                 (WidthInfo::ZWJ_EMOJI_PRESENTATION, _)
-                    if lookup_width_cjk(c).1 == WidthInfo::EMOJI_PRESENTATION =>
+                    if matches!(lookup_width_cjk(c).1, WidthInfo::EMOJI_PRESENTATION) =>
                 {
                     return (0, WidthInfo::EMOJI_PRESENTATION)
                 }
@@ -685,49 +753,79 @@ fn width_in_str_cjk(c: char, mut next_info: WidthInfo) -> (i8, WidthInfo) {
     }
 }
 
+/// Returns the displayed width of `s` in columns, as
+/// [`width_cjk`](crate::UnicodeWidthStr::width_cjk) does.
 #[cfg(feature = "cjk")]
 #[inline]
-pub fn str_width_cjk(s: &str) -> usize {
-    s.chars()
-        .rfold(
-            (0, WidthInfo::DEFAULT),
-            |(sum, next_info), c| -> (usize, WidthInfo) {
-                let (add, info) = width_in_str_cjk(c, next_info);
-                (sum.wrapping_add_signed(isize::from(add)), info)
-            },
-        )
-        .0
+pub const fn str_width_cjk(s: &str) -> usize {
+    if is_printable_ascii(s) {
+        return s.len();
+    }
+    // Characters are measured from the end, each depending on those after it
+    let mut sum: usize = 0;
+    let mut next_info = WidthInfo::DEFAULT;
+    let mut rest = s.as_bytes();
+    while let Some((c, init)) = split_last_char(rest) {
+        let (add, info) = width_in_str_cjk(c, next_info);
+        sum = sum.wrapping_add_signed(add as isize);
+        next_info = info;
+        rest = init;
+    }
+    sum
 }
 
 /// Whether this character is a zero-width character with
 /// `Joining_Type=Transparent`. Used by the Alef-Lamed ligatures.
 /// See also [`is_ligature_transparent`], a near-subset of this (only ZWJ is excepted)
 /// which is transparent for non-Arabic ligatures.
-fn is_transparent_zero_width(c: char) -> bool {
+const fn is_transparent_zero_width(c: char) -> bool {
     if lookup_width(c).0 != 0 {
         // Not zero-width
         false
     } else {
-        let cp: u32 = c.into();
-        NON_TRANSPARENT_ZERO_WIDTHS
-            .binary_search_by(|&(lo, hi)| {
-                let lo = u32::from_le_bytes([lo[0], lo[1], lo[2], 0]);
-                let hi = u32::from_le_bytes([hi[0], hi[1], hi[2], 0]);
-                if cp < lo {
-                    Ordering::Greater
-                } else if cp > hi {
-                    Ordering::Less
-                } else {
-                    Ordering::Equal
-                }
-            })
-            .is_err()
+        !in_u24_ranges(c as u32, &NON_TRANSPARENT_ZERO_WIDTHS)
     }
 }
 
+/// Whether `cp` is in one of the sorted, inclusive `ranges` of little endian
+/// 24-bit code points.
+const fn in_u24_ranges(cp: u32, ranges: &[([u8; 3], [u8; 3])]) -> bool {
+    let (mut lo, mut hi) = (0, ranges.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let (start, end) = ranges[mid];
+        if cp < u32::from_le_bytes([start[0], start[1], start[2], 0]) {
+            hi = mid;
+        } else if cp > u32::from_le_bytes([end[0], end[1], end[2], 0]) {
+            lo = mid + 1;
+        } else {
+            return true;
+        }
+    }
+    false
+}
+
+/// Whether `bottom_bits` is in one of the sorted, inclusive `ranges` of a
+/// byte range leaf.
+const fn in_u8_ranges(bottom_bits: u8, ranges: &[(u8, u8)]) -> bool {
+    let (mut lo, mut hi) = (0, ranges.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let (start, end) = ranges[mid];
+        if bottom_bits < start {
+            hi = mid;
+        } else if bottom_bits > end {
+            lo = mid + 1;
+        } else {
+            return true;
+        }
+    }
+    false
+}
+
 /// Whether this character is a default-ignorable combining mark
 /// or ZWJ. These characters won't interrupt non-Arabic ligatures.
-fn is_ligature_transparent(c: char) -> bool {
+const fn is_ligature_transparent(c: char) -> bool {
     matches!(c, '\u{34F}' | '\u{17B4}'..='\u{17B5}' | '\u{180B}'..='\u{180D}' | '\u{180F}' | '\u{200D}' | '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}')
 }
 
@@ -735,22 +833,8 @@ fn is_ligature_transparent(c: char) -> bool {
 /// U+0338 COMBINING LONG SOLIDUS OVERLAY
 /// on its base character.
 #[cfg(feature = "cjk")]
-fn is_solidus_transparent(c: char) -> bool {
-    let cp: u32 = c.into();
-    is_ligature_transparent(c)
-        || SOLIDUS_TRANSPARENT
-            .binary_search_by(|&(lo, hi)| {
-                let lo = u32::from_le_bytes([lo[0], lo[1], lo[2], 0]);
-                let hi = u32::from_le_bytes([hi[0], hi[1], hi[2], 0]);
-                if cp < lo {
-                    Ordering::Greater
-                } else if cp > hi {
-                    Ordering::Less
-                } else {
-                    Ordering::Equal
-                }
-            })
-            .is_ok()
+const fn is_solidus_transparent(c: char) -> bool {
+    is_ligature_transparent(c) || in_u24_ranges(c as u32, &SOLIDUS_TRANSPARENT)
 }
 
 /// Whether this character forms an [emoji presentation sequence]
@@ -758,8 +842,8 @@ fn is_solidus_transparent(c: char) -> bool {
 /// when followed by `'\u{FEOF}'`.
 /// Emoji presentation sequences are considered to have width 2.
 #[inline]
-pub fn starts_emoji_presentation_seq(c: char) -> bool {
-    let cp: u32 = c.into();
+pub const fn starts_emoji_presentation_seq(c: char) -> bool {
+    let cp = c as u32;
     // First level of lookup uses all but 10 LSB
     let top_bits = cp >> 10;
     let idx_of_leaf: usize = match top_bits {
@@ -774,7 +858,7 @@ pub fn starts_emoji_presentation_seq(c: char) -> bool {
     };
     // Extract the 3-9th (0-indexed) least significant bits of `cp`,
     // and use them to index into `leaf_row`.
-    let idx_within_leaf = usize::try_from((cp >> 3) & 0x7F).unwrap();
+    let idx_within_leaf = ((cp >> 3) & 0x7F) as usize;
     let leaf_byte = EMOJI_PRESENTATION_LEAVES.0[idx_of_leaf][idx_within_leaf];
     // Use the 3 LSB of `cp` to index into `leaf_byte`.
     ((leaf_byte >> (cp & 7)) & 1) == 1
@@ -785,8 +869,8 @@ pub fn starts_emoji_presentation_seq(c: char) -> bool {
 /// when followed by `'\u{FEOE}'`, and is not ideographic.
 /// Such sequences are considered to have width 1.
 #[inline]
-pub fn starts_non_ideographic_text_presentation_seq(c: char) -> bool {
-    let cp: u32 = c.into();
+pub const fn starts_non_ideographic_text_presentation_seq(c: char) -> bool {
+    let cp = c as u32;
     // First level of lookup uses all but 8 LSB
     let top_bits = cp >> 8;
     let leaf: &[(u8, u8)] = match top_bits {
@@ -803,23 +887,13 @@ pub fn starts_non_ideographic_text_presentation_seq(c: char) -> bool {
         _ => return false,
     };
 
-    let bottom_bits = (cp & 0xFF) as u8;
-    leaf.binary_search_by(|&(lo, hi)| {
-        if bottom_bits < lo {
-            Ordering::Greater
-        } else if bottom_bits > hi {
-            Ordering::Less
-        } else {
-            Ordering::Equal
-        }
-    })
-    .is_ok()
+    in_u8_ranges((cp & 0xFF) as u8, leaf)
 }
 
 /// Returns `true` if `c` is an `Emoji_Modifier_Base`.
 #[inline]
-pub fn is_emoji_modifier_base(c: char) -> bool {
-    let cp: u32 = c.into();
+pub const fn is_emoji_modifier_base(c: char) -> bool {
+    let cp = c as u32;
     // First level of lookup uses all but 8 LSB
     let top_bits = cp >> 8;
     let leaf: &[(u8, u8)] = match top_bits {
@@ -834,17 +908,7 @@ pub fn is_emoji_modifier_base(c: char) -> bool {
         _ => return false,
     };
 
-    let bottom_bits = (cp & 0xFF) as u8;
-    leaf.binary_search_by(|&(lo, hi)| {
-        if bottom_bits < lo {
-            Ordering::Greater
-        } else if bottom_bits > hi {
-            Ordering::Less
-        } else {
-            Ordering::Equal
-        }
-    })
-    .is_ok()
+    in_u8_ranges((cp & 0xFF) as u8, leaf)
 }
 
 #[repr(align(32))]
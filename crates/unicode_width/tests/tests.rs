@@ -13,7 +13,16 @@ use std::{
     io::{BufRead, BufReader},
 };
 
-use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use unicode_width::{
+    ansi_segments, pad_to_width, pad_to_width_ansi, truncate_to_width, truncate_to_width_ansi,
+    truncate_to_width_ansi_for, width_bytes, Align, AmbiguousWidth, AnsiSegment, ControlWidth,
+    InvalidWidth, Profile, UnicodeWidthChar, UnicodeWidthStr, WidthOptions,
+};
+#[cfg(feature = "cjk")]
+use unicode_width::{
+    pad_to_width_ansi_cjk, pad_to_width_cjk, truncate_to_width_ansi_cjk_for, truncate_to_width_cjk,
+    width_bytes_cjk,
+};
 
 macro_rules! assert_width {
     ($s:expr, $nocjk:expr, $cjk:expr $(,)?) => {{
@@ -595,6 +604,555 @@ fn ambiguous_line_break() {
     assert_width!("\u{2780}", 1, 2);
 }
 
+fn clusters(s: &str) -> Vec<(usize, &str, usize)> {
+    s.width_indices().collect()
+}
+
+#[test]
+fn test_width_indices() {
+    assert_eq!(clusters(""), []);
+    assert_eq!(clusters("ab"), [(0, "a", 1), (1, "b", 1)]);
+    assert_eq!(
+        clusters("a\r\nb"),
+        [(0, "a", 1), (1, "\r\n", 1), (3, "b", 1)]
+    );
+    assert_eq!(clusters("\n\u{301}"), [(0, "\n", 1), (1, "\u{301}", 0)]);
+    assert_eq!(clusters("e\u{301}x"), [(0, "e\u{301}", 1), (3, "x", 1)]);
+    assert_eq!(clusters("👨‍👩‍👧x"), [(0, "👨‍👩‍👧", 2), (18, "x", 1)]);
+    assert_eq!(clusters("👍🏽"), [(0, "👍🏽", 2)]);
+    assert_eq!(clusters("🇰🇷🇯🇵"), [(0, "🇰🇷", 2), (8, "🇯🇵", 2)]);
+    assert_eq!(clusters("🇰🇷🇯"), [(0, "🇰🇷", 2), (8, "🇯", 1)]);
+    assert_eq!(
+        clusters("\u{1100}\u{1161}\u{11A8}한"),
+        [(0, "\u{1100}\u{1161}\u{11A8}", 2), (9, "한", 2)]
+    );
+    assert_eq!(clusters("\u{1100}\u{1100}"), [(0, "\u{1100}\u{1100}", 4)]);
+    assert_eq!(clusters("\u{0644}\u{0627}"), [(0, "\u{0644}\u{0627}", 1)]);
+}
+
+#[cfg(feature = "cjk")]
+#[test]
+fn test_width_indices_cjk() {
+    let widths = |s: &str| s.width_indices_cjk().map(|(_, _, w)| w).collect::<Vec<_>>();
+    assert_eq!(widths("\u{2616}a"), [2, 1]);
+    assert_eq!(widths("\u{00A1}\u{0301}"), [2]);
+}
+
+#[test]
+fn test_width_indices_sum() {
+    for s in [
+        "Hello, 世界!",
+        "🇮🇱🕊️🇵🇸",
+        "🇵🇸\u{200D}🕊️\u{200D}🇮🇱",
+        "\u{1F46A}\u{200D}\u{200D}\u{1F3FB}",
+        "\u{05D0}\u{200D}\u{05DC}",
+        "\u{A4FC}\u{A4F8}\u{A4FC}",
+        "\u{2D31}\u{2D7F}\u{2D31}",
+        "क्\u{200D}ष",
+        "*\u{FE0F}\u{20E3}\u{200D}👪",
+        "🇦👪\u{200D}🏿\u{200D}👪🏻\u{200D}Ⓜ️\u{200D}*\u{FE0F}\u{200D}🇦🇦\u{200D}🏴󠁧󠁢󠁷󠁬󠁳󠁿\u{200D}👪",
+    ] {
+        let sum: usize = s.width_indices().map(|(_, _, w)| w).sum();
+        assert_eq!(sum, s.width(), "{:?}", s);
+        let joined: String = s.width_indices().map(|(_, c, _)| c).collect();
+        assert_eq!(joined, s);
+        #[cfg(feature = "cjk")]
+        {
+            let sum: usize = s.width_indices_cjk().map(|(_, _, w)| w).sum();
+            assert_eq!(sum, s.width_cjk(), "{:?}", s);
+        }
+    }
+}
+
+#[test]
+fn emoji_test_file_clusters() {
+    let norm_file = BufReader::new(
+        File::open("tests/emoji-test.txt")
+            .expect("run `unicode.py` first to download `emoji-test.txt`"),
+    );
+    for line in norm_file.lines() {
+        let line = line.unwrap();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (cps, status) = line.split_once(';').unwrap();
+        if status.trim().starts_with("fully-qualified") {
+            let emoji: String = cps
+                .trim()
+                .split(' ')
+                .map(|s| char::try_from(u32::from_str_radix(s, 16).unwrap()).unwrap())
+                .collect();
+            assert_eq!(clusters(&emoji), [(0, emoji.as_str(), 2)]);
+        }
+    }
+}
+
+#[test]
+fn test_truncate_to_width() {
+    let t = truncate_to_width("hello", 10, Some("…"));
+    assert_eq!(
+        (t.to_string(), t.width(), t.is_truncated()),
+        ("hello".into(), 5, false)
+    );
+    let t = truncate_to_width("hello world", 8, Some("…"));
+    assert_eq!((t.text(), t.suffix(), t.width()), ("hello w", "…", 8));
+    assert!(t.is_truncated());
+
+    // Wide characters are never split, leaving a column free
+    assert_eq!(truncate_to_width("a한국", 4, None).to_string(), "a한");
+    assert_eq!(truncate_to_width("a한국", 4, Some(">")).to_string(), "a한>");
+    assert_eq!(truncate_to_width("👨‍👩‍👧👨‍👩‍👧", 3, None).to_string(), "👨‍👩‍👧");
+    assert_eq!(truncate_to_width("🇰🇷🇯🇵", 3, None).to_string(), "🇰🇷");
+    assert_eq!(
+        truncate_to_width("e\u{301}e\u{301}", 1, None).to_string(),
+        "e\u{301}"
+    );
+
+    // An oversized suffix is cut too
+    let t = truncate_to_width("hello", 2, Some("[...]"));
+    assert_eq!((t.text(), t.suffix(), t.width()), ("", "[.", 2));
+    assert_eq!(truncate_to_width("hello", 0, Some("…")).to_string(), "");
+}
+
+#[cfg(feature = "cjk")]
+#[test]
+fn test_truncate_to_width_cjk() {
+    assert_eq!(truncate_to_width("““a", 2, None).to_string(), "““");
+    assert_eq!(truncate_to_width_cjk("““a", 2, None).to_string(), "“");
+    assert_eq!(truncate_to_width_cjk("abc", 2, Some("…")).to_string(), "…");
+}
+
+#[test]
+fn test_pad_to_width() {
+    assert_eq!(pad_to_width("ab", 5, Align::Left).to_string(), "ab   ");
+    assert_eq!(pad_to_width("ab", 5, Align::Right).to_string(), "   ab");
+    assert_eq!(pad_to_width("ab", 5, Align::Center).to_string(), " ab  ");
+    assert_eq!(pad_to_width("한국", 6, Align::Center).to_string(), " 한국 ");
+    assert_eq!(pad_to_width("한국", 3, Align::Left).to_string(), "한국");
+    assert_eq!(pad_to_width("한국", 3, Align::Left).width(), 4);
+    assert_eq!(pad_to_width("ab", 5, Align::Right).width(), 5);
+}
+
+#[cfg(feature = "cjk")]
+#[test]
+fn test_pad_to_width_cjk() {
+    assert_eq!(pad_to_width("“", 3, Align::Right).to_string(), "  “");
+    assert_eq!(pad_to_width_cjk("“", 3, Align::Right).to_string(), " “");
+    assert_eq!(pad_to_width_cjk("“", 3, Align::Right).width(), 3);
+}
+
+#[test]
+fn test_ansi_segments() {
+    use AnsiSegment::{Escape, Text};
+
+    let segments = |s| ansi_segments(s).collect::<Vec<_>>();
+    assert_eq!(segments(""), []);
+    assert_eq!(segments("plain"), [Text("plain")]);
+    assert_eq!(
+        segments("a\x1b[1;38;5;196mb\x1b[mc"),
+        [
+            Text("a"),
+            Escape("\x1b[1;38;5;196m"),
+            Text("b"),
+            Escape("\x1b[m"),
+            Text("c"),
+        ]
+    );
+    // OSC terminated by ST and by BEL
+    assert_eq!(
+        segments("\x1b]8;;http://a.b\x1b\\x\x1b]0;title\x07y"),
+        [
+            Escape("\x1b]8;;http://a.b\x1b\\"),
+            Text("x"),
+            Escape("\x1b]0;title\x07"),
+            Text("y"),
+        ]
+    );
+    // 8-bit CSI and OSC
+    assert_eq!(
+        segments("\u{9B}31mx\u{9D}0;t\u{9C}"),
+        [Escape("\u{9B}31m"), Text("x"), Escape("\u{9D}0;t\u{9C}")]
+    );
+    // Two-character escapes, charset designation and a stray ESC
+    assert_eq!(
+        segments("\x1b7\x1b(Bx\x1b"),
+        [Escape("\x1b7"), Escape("\x1b(B"), Text("x"), Escape("\x1b")]
+    );
+    // Unterminated sequences run to the end
+    assert_eq!(
+        segments("x\x1b]0;title"),
+        [Text("x"), Escape("\x1b]0;title")]
+    );
+    assert_eq!(segments("x\x1b[31"), [Text("x"), Escape("\x1b[31")]);
+    // A CSI broken off by a non-CSI character
+    assert_eq!(segments("\x1b[3한"), [Escape("\x1b[3"), Text("한")]);
+}
+
+#[test]
+fn test_width_ansi() {
+    assert_eq!("".width_ansi(), 0);
+    assert_eq!("\x1b[31m".width(), 5);
+    assert_eq!("\x1b[31m".width_ansi(), 0);
+    assert_eq!("\x1b[1;31m한국어\x1b[0m".width_ansi(), 6);
+    assert_eq!(
+        "\x1b]8;id=1;https://example.com/\x1b\\docs\x1b]8;;\x1b\\".width_ansi(),
+        4
+    );
+    #[cfg(feature = "cjk")]
+    assert_eq!("\x1b[31m“x”\x1b[0m".width_ansi_cjk(), 5);
+}
+
+#[test]
+fn test_truncate_to_width_ansi() {
+    let line = "\x1b[31m한국어\x1b[0m text";
+    let t = truncate_to_width_ansi(line, 20, Some("…"));
+    assert_eq!(
+        (t.to_string(), t.width(), t.is_truncated()),
+        (line.into(), 11, false)
+    );
+
+    let t = truncate_to_width_ansi(line, 5, Some("…"));
+    assert_eq!(t.to_string(), "\x1b[31m한국…\x1b[0m");
+    assert_eq!((t.text(), t.width()), ("\x1b[31m한국", 5));
+
+    // Escapes before the cut are kept with the text
+    assert_eq!(
+        truncate_to_width_ansi(line, 7, None).to_string(),
+        "\x1b[31m한국어\x1b[0m "
+    );
+    assert_eq!(
+        truncate_to_width_ansi("\x1b[1mab\x1b[4mcd\x1b[0m", 3, Some("\x1b[2m>")).to_string(),
+        "\x1b[1mab\x1b[4m\x1b[2m>\x1b[0m"
+    );
+    // Plain truncation treats escapes as text
+    assert_eq!(
+        truncate_to_width("\x1b[31mred", 4, None).to_string(),
+        "\x1b[31"
+    );
+}
+
+#[test]
+fn test_pad_to_width_ansi() {
+    let red = "\x1b[31m한\x1b[0m";
+    assert_eq!(
+        pad_to_width_ansi(red, 4, Align::Left).to_string(),
+        format!("{}  ", red)
+    );
+    assert_eq!(pad_to_width_ansi(red, 4, Align::Right).width(), 4);
+    assert_eq!(pad_to_width(red, 4, Align::Right).to_string(), red);
+    #[cfg(feature = "cjk")]
+    assert_eq!(
+        pad_to_width_ansi_cjk("\x1b[1m“\x1b[0m", 3, Align::Center).to_string(),
+        "\x1b[1m“\x1b[0m "
+    );
+}
+
+#[test]
+fn test_width_with_defaults() {
+    let options = WidthOptions::default();
+    assert_eq!(options, WidthOptions::new());
+    for s in [
+        "",
+        "abc",
+        "한국어",
+        "\r\n",
+        "a\x07b",
+        "👨‍👩‍👧",
+        "\u{0644}\u{0627}",
+    ] {
+        assert_eq!(s.width_with(&options), s.width(), "{:?}", s);
+    }
+    for c in ['a', '한', '\u{301}', '\x07', '\u{85}'] {
+        assert_eq!(c.width_with(&options), c.width(), "{:?}", c);
+    }
+}
+
+#[test]
+fn test_width_with_tabs() {
+    let tabs = |tab_size| WidthOptions {
+        tab_size,
+        ..WidthOptions::new()
+    };
+    assert_eq!("\t".width_with(&tabs(8)), 8);
+    assert_eq!("abc\t".width_with(&tabs(8)), 8);
+    assert_eq!("abcdefgh\t".width_with(&tabs(8)), 16);
+    assert_eq!("한\t국\t".width_with(&tabs(4)), 8);
+    assert_eq!("\t\t".width_with(&tabs(1)), 2);
+    assert_eq!("a\tb".width_with(&tabs(0)), "a\tb".width());
+    assert_eq!('\t'.width_with(&tabs(4)), Some(4));
+    assert_eq!('\t'.width_with(&tabs(0)), None);
+}
+
+#[test]
+fn test_width_with_controls() {
+    let control = |control| WidthOptions {
+        control,
+        ..WidthOptions::new()
+    };
+    let caret = control(ControlWidth::Caret);
+    assert_eq!('\0'.width_with(&caret), Some(2));
+    assert_eq!('\x1b'.width_with(&caret), Some(2));
+    assert_eq!('\x7f'.width_with(&caret), Some(2));
+    assert_eq!('\u{85}'.width_with(&caret), Some(3));
+    assert_eq!('a'.width_with(&caret), Some(1));
+    assert_eq!("a\x1b[0m\r\n".width_with(&caret), 1 + 2 + 3 + 2 + 2);
+    assert_eq!("\t\x07".width_with(&caret), 10);
+
+    let replacement = control(ControlWidth::Replacement);
+    assert_eq!('\x07'.width_with(&replacement), Some(1));
+    assert_eq!('\u{9f}'.width_with(&replacement), Some(1));
+    assert_eq!("a\x1b[0m\r\n".width_with(&replacement), 1 + 1 + 3 + 1 + 1);
+    // Ligatures on either side of a control are still measured
+    assert_eq!(
+        "\u{0644}\u{0627}\x07\u{0644}\u{0627}".width_with(&replacement),
+        3
+    );
+}
+
+#[cfg(feature = "cjk")]
+#[test]
+fn test_width_with_ambiguous() {
+    let wide = WidthOptions {
+        ambiguous: AmbiguousWidth::Wide,
+        ..WidthOptions::new()
+    };
+    assert_eq!('“'.width_with(&wide), Some(2));
+    assert_eq!("“x”".width_with(&wide), "“x”".width_cjk());
+    assert_eq!("“\t".width_with(&wide), 8);
+    assert_eq!(
+        "“x”".width_with(&WidthOptions {
+            ambiguous: AmbiguousWidth::Narrow,
+            ..wide
+        }),
+        3
+    );
+}
+
+#[cfg(not(feature = "cjk"))]
+#[test]
+fn test_width_with_ambiguous_without_cjk() {
+    let wide = WidthOptions {
+        ambiguous: AmbiguousWidth::Wide,
+        ..WidthOptions::new()
+    };
+    assert_eq!('“'.width_with(&wide), Some(1));
+    assert_eq!("“x”".width_with(&wide), "“x”".width());
+}
+
+#[test]
+fn test_ambiguous_width_from_locale() {
+    let narrow = AmbiguousWidth::Narrow;
+    assert_eq!(AmbiguousWidth::from_locale(""), narrow);
+    assert_eq!(AmbiguousWidth::from_locale("C"), narrow);
+    assert_eq!(AmbiguousWidth::from_locale("en_US.UTF-8"), narrow);
+    assert_eq!(AmbiguousWidth::from_locale("kok_IN"), narrow);
+    for name in [
+        "ko_KR.UTF-8",
+        "ja_JP",
+        "zh_CN.GB18030",
+        "zh_TW.UTF-8@radical",
+        "ko",
+    ] {
+        assert_eq!(
+            AmbiguousWidth::from_locale(name),
+            AmbiguousWidth::Wide,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_ambiguous_width_resolve() {
+    assert_eq!(AmbiguousWidth::Narrow.resolve(), AmbiguousWidth::Narrow);
+    assert_eq!(AmbiguousWidth::Wide.resolve(), AmbiguousWidth::Wide);
+    assert_ne!(AmbiguousWidth::Auto.resolve(), AmbiguousWidth::Auto);
+
+    let auto = WidthOptions {
+        ambiguous: AmbiguousWidth::Auto,
+        ..WidthOptions::new()
+    };
+    let resolved = WidthOptions {
+        ambiguous: AmbiguousWidth::Auto.resolve(),
+        ..WidthOptions::new()
+    };
+    assert_eq!("“x”".width_with(&auto), "“x”".width_with(&resolved));
+    assert_eq!('“'.width_with(&auto), '“'.width_with(&resolved));
+}
+
+#[test]
+fn test_printable_ascii_fast_path() {
+    let all: String = (' '..='~').collect();
+    assert_eq!(all.width(), 95);
+    assert_eq!(truncate_to_width(&all, 16, Some("…")).text(), &all[..15]);
+    assert_eq!(truncate_to_width(&all, 95, Some("…")).text(), all);
+    // One other character anywhere, in or after a full 16 byte chunk
+    for len in 0..40 {
+        for pos in 0..=len {
+            for (c, w) in [
+                ('\t', 1),
+                ('\x7f', 1),
+                ('\u{e9}', 1),
+                ('한', 2),
+                ('\u{301}', 0),
+            ] {
+                let mut s = "a".repeat(len);
+                s.insert(pos, c);
+                assert_eq!(s.width(), len + w, "{:?}", s);
+                #[cfg(feature = "cjk")]
+                assert_eq!(s.width_cjk(), len + w, "{:?}", s);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_const_width() {
+    const BANNER: &str = "== 로그 \u{0644}\u{0627} 👩\u{200D}🔬 ==";
+    const WIDTH: usize = unicode_width::str_width(BANNER);
+    const _: () = assert!(unicode_width::str_width("\r\n") == 1);
+    const _: () = assert!(matches!(unicode_width::single_char_width('한'), Some(2)));
+    assert_eq!(WIDTH, BANNER.width());
+    assert_eq!(WIDTH, 15);
+    #[cfg(feature = "cjk")]
+    {
+        const WIDTH_CJK: usize = unicode_width::str_width_cjk("“x”");
+        const _: () = assert!(matches!(unicode_width::single_char_width_cjk('“'), Some(2)));
+        assert_eq!(WIDTH_CJK, 5);
+    }
+}
+
+#[test]
+fn test_width_bytes() {
+    for s in [
+        "",
+        "abc",
+        "한국어",
+        "\r\n",
+        "\u{0644}\u{0627}",
+        "👩\u{200D}🔬",
+        "“x”",
+    ] {
+        for invalid in [InvalidWidth::Replacement, InvalidWidth::PerByte(4)] {
+            assert_eq!(width_bytes(s.as_bytes(), invalid), s.width(), "{:?}", s);
+            #[cfg(feature = "cjk")]
+            assert_eq!(
+                width_bytes_cjk(s.as_bytes(), invalid),
+                s.width_cjk(),
+                "{:?}",
+                s
+            );
+        }
+    }
+
+    // A truncated 3 byte sequence is one invalid sequence of 2 bytes
+    let bytes = b"a\xED\x95b\xFF\xFEc";
+    assert_eq!(width_bytes(bytes, InvalidWidth::Replacement), 3 + 3);
+    assert_eq!(
+        width_bytes(bytes, InvalidWidth::Replacement),
+        String::from_utf8_lossy(bytes).width()
+    );
+    assert_eq!(width_bytes(bytes, InvalidWidth::PerByte(1)), 3 + 4);
+    assert_eq!(width_bytes(bytes, InvalidWidth::PerByte(4)), 3 + 16);
+    assert_eq!(width_bytes(bytes, InvalidWidth::PerByte(0)), 3);
+}
+
+#[test]
+fn test_width_for_profiles() {
+    let sequences = [
+        ("❤\u{FE0F}", 2, 1),
+        ("⌚\u{FE0E}", 1, 2),
+        ("👍🏽", 2, 4),
+        ("👩\u{200D}🔬", 2, 4),
+        ("🇰🇷", 2, 2),
+        ("a한\u{0644}\u{0627}", 4, 4),
+    ];
+    for (s, joined, split) in sequences {
+        assert_eq!(s.width_for(Profile::Unicode), s.width(), "{:?}", s);
+        for profile in [
+            Profile::Unicode,
+            Profile::Kitty,
+            Profile::WezTerm,
+            Profile::WindowsTerminal,
+        ] {
+            assert_eq!(s.width_for(profile), joined, "{:?} {:?}", s, profile);
+        }
+        for profile in [Profile::Xterm, Profile::Vte] {
+            assert_eq!(s.width_for(profile), split, "{:?} {:?}", s, profile);
+        }
+    }
+    assert_eq!("x👍🏽y❤\u{FE0F}".width_for(Profile::Xterm), 1 + 4 + 1 + 1);
+    #[cfg(feature = "cjk")]
+    assert_eq!("“❤\u{FE0F}”".width_cjk_for(Profile::Xterm), 5);
+}
+
+#[test]
+fn test_truncate_for_profiles() {
+    let line = "\x1b[1m👩\u{200D}🔬 lab\x1b[0m";
+    assert_eq!(
+        truncate_to_width_ansi_for(line, 3, Some("…"), Profile::WezTerm).to_string(),
+        "\x1b[1m👩\u{200D}🔬…\x1b[0m"
+    );
+    assert_eq!(
+        truncate_to_width_ansi_for(line, 3, Some("…"), Profile::Xterm).to_string(),
+        "\x1b[1m…\x1b[0m"
+    );
+    #[cfg(feature = "cjk")]
+    assert_eq!(
+        truncate_to_width_ansi_cjk_for("👍🏽“”", 6, None, Profile::Xterm).to_string(),
+        "👍🏽“"
+    );
+}
+
+#[test]
+fn test_profile_from_vars() {
+    let cases = [
+        (&[("TERM", "xterm-kitty")][..], Profile::Kitty),
+        (
+            &[("TERM", "xterm-kitty"), ("VTE_VERSION", "7600")],
+            Profile::Kitty,
+        ),
+        (
+            &[("TERM", "xterm-256color"), ("TERM_PROGRAM", "WezTerm")],
+            Profile::WezTerm,
+        ),
+        (&[("TERM", "wezterm")], Profile::WezTerm),
+        (
+            &[("TERM", "xterm-256color"), ("WT_SESSION", "4f2a")],
+            Profile::WindowsTerminal,
+        ),
+        (
+            &[("TERM", "xterm-256color"), ("VTE_VERSION", "7600")],
+            Profile::Vte,
+        ),
+        (
+            &[("TERM", "xterm"), ("XTERM_VERSION", "XTerm(390)")],
+            Profile::Xterm,
+        ),
+        (&[("XTERM_VERSION", ""), ("VTE_VERSION", "")], Profile::Vte),
+        (
+            &[("TERM", "xterm-256color"), ("TERM_PROGRAM", "tmux")],
+            Profile::Unicode,
+        ),
+        (&[], Profile::Unicode),
+    ];
+    for (vars, profile) in cases {
+        let var = |name: &str| vars.iter().find(|(var, _)| *var == name).map(|(_, v)| *v);
+        assert_eq!(Profile::from_vars(var), profile, "{:?}", vars);
+    }
+}
+
+#[test]
+fn test_profile_resolve() {
+    assert_eq!(Profile::Kitty.resolve(), Profile::Kitty);
+    assert_ne!(Profile::Auto.resolve(), Profile::Auto);
+    assert_eq!(
+        "👍🏽".width_for(Profile::Auto),
+        "👍🏽".width_for(Profile::Auto.resolve())
+    );
+}
+
 // Test traits are unsealed
 
 #[cfg(feature = "cjk")]
@@ -610,6 +1168,10 @@ impl UnicodeWidthChar for Foo {
     fn width_cjk(self) -> Option<usize> {
         Some(0)
     }
+
+    fn width_with(self, _: &unicode_width::WidthOptions) -> Option<usize> {
+        Some(0)
+    }
 }
 
 #[cfg(feature = "cjk")]
@@ -621,4 +1183,32 @@ impl UnicodeWidthStr for Foo {
     fn width_cjk(&self) -> usize {
         0
     }
+
+    fn width_indices(&self) -> unicode_width::WidthIndices<'_> {
+        "".width_indices()
+    }
+
+    fn width_indices_cjk(&self) -> unicode_width::WidthIndices<'_> {
+        "".width_indices_cjk()
+    }
+
+    fn width_ansi(&self) -> usize {
+        0
+    }
+
+    fn width_ansi_cjk(&self) -> usize {
+        0
+    }
+
+    fn width_for(&self, _: unicode_width::Profile) -> usize {
+        0
+    }
+
+    fn width_cjk_for(&self, _: unicode_width::Profile) -> usize {
+        0
+    }
+
+    fn width_with(&self, _: &unicode_width::WidthOptions) -> usize {
+        0
+    }
 }
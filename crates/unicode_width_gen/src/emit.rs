@@ -0,0 +1,478 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    io,
+};
+
+use crate::{
+    invalid_data,
+    tables::{ranges, Special, Tables, Width, NUM_CODEPOINTS},
+};
+
+/// `rustfmt`'s line width.
+const MAX_WIDTH: usize = 100;
+
+/// Code points covered by each entry of `WIDTH_ROOT`.
+const ROOT_SHIFT: u32 = 13;
+/// Code points covered by each entry of a `WIDTH_MIDDLE` sub-table.
+const MIDDLE_SHIFT: u32 = 7;
+/// Entries in `WIDTH_ROOT`, which is padded with zeros.
+const ROOT_LEN: usize = 256;
+
+/// The multi-level width lookup tables. Sub-tables are shared between the
+/// plain and CJK tables; the ones only the CJK tables use come last.
+struct Packed {
+    root: Vec<u8>,
+    root_cjk: Vec<u8>,
+    middle: Vec<Vec<u8>>,
+    /// Number of `middle` sub-tables the plain root uses
+    middle_len: usize,
+    leaves: Vec<Vec<u8>>,
+    /// Number of `leaves` the plain middle sub-tables use
+    leaves_len: usize,
+}
+
+/// Appends `table` to `tables` unless an equal one is already there, and
+/// returns its index.
+fn intern(tables: &mut Vec<Vec<u8>>, table: Vec<u8>) -> io::Result<u8> {
+    let index = match tables.iter().position(|t| *t == table) {
+        Some(index) => index,
+        None => {
+            tables.push(table);
+            tables.len() - 1
+        }
+    };
+    u8::try_from(index).map_err(|_| invalid_data("more than 256 sub-tables"))
+}
+
+/// Packs `widths` into a root table, adding the sub-tables it needs.
+fn pack_widths(
+    widths: &[Width],
+    middle: &mut Vec<Vec<u8>>,
+    leaves: &mut Vec<Vec<u8>>,
+) -> io::Result<Vec<u8>> {
+    let leaf_indices = widths
+        .chunks(1 << MIDDLE_SHIFT)
+        .map(|chunk| {
+            let leaf = chunk
+                .chunks(4)
+                .map(|w| w.iter().rev().fold(0, |byte, w| byte << 2 | w.code()))
+                .collect();
+            intern(leaves, leaf)
+        })
+        .collect::<io::Result<Vec<u8>>>()?;
+    let mut root = leaf_indices
+        .chunks(1 << (ROOT_SHIFT - MIDDLE_SHIFT))
+        .map(|chunk| intern(middle, chunk.to_vec()))
+        .collect::<io::Result<Vec<u8>>>()?;
+    root.resize(ROOT_LEN, 0);
+    Ok(root)
+}
+
+fn pack(tables: &Tables) -> io::Result<Packed> {
+    if tables.widths.len() != NUM_CODEPOINTS || tables.widths_cjk.len() != NUM_CODEPOINTS {
+        return Err(invalid_data("widths don't cover every code point"));
+    }
+    let mut middle = Vec::new();
+    let mut leaves = Vec::new();
+    let root = pack_widths(&tables.widths, &mut middle, &mut leaves)?;
+    let (middle_len, leaves_len) = (middle.len(), leaves.len());
+    let root_cjk = pack_widths(&tables.widths_cjk, &mut middle, &mut leaves)?;
+    Ok(Packed {
+        root,
+        root_cjk,
+        middle,
+        middle_len,
+        leaves,
+        leaves_len,
+    })
+}
+
+/// `bytes` as hex literals, wrapped the way `rustfmt` does at `indent`.
+fn byte_lines(out: &mut String, bytes: &[u8], indent: usize) {
+    let per_line = (MAX_WIDTH - indent + 1) / "0x00, ".len();
+    for line in bytes.chunks(per_line) {
+        let line: Vec<_> = line.iter().map(|b| format!("0x{b:02X},")).collect();
+        writeln!(out, "{:indent$}{}", "", line.join(" ")).unwrap();
+    }
+}
+
+fn root_table(out: &mut String, name: &str, comment_count: usize, cjk: bool, root: &[u8]) {
+    writeln!(
+        out,
+        "/// Autogenerated. {comment_count} sub-table(s). Consult [`lookup_width`] for layout info.)"
+    )
+    .unwrap();
+    if cjk {
+        out.push_str("#[cfg(feature = \"cjk\")]\n");
+    }
+    writeln!(
+        out,
+        "static {name}: Align128<[u8; {ROOT_LEN}]> = Align128(["
+    )
+    .unwrap();
+    byte_lines(out, root, 4);
+    out.push_str("]);\n");
+}
+
+/// A table of sub-tables, the ones after `len` only being used by the CJK
+/// tables.
+fn sub_tables(
+    out: &mut String,
+    name: &str,
+    align: usize,
+    comment_count: usize,
+    tables: &[Vec<u8>],
+    len: usize,
+) {
+    let size = tables.first().map_or(0, Vec::len);
+    writeln!(out, "#[cfg(feature = \"cjk\")]").unwrap();
+    writeln!(out, "const {name}_LEN: usize = {};", tables.len()).unwrap();
+    writeln!(out, "#[cfg(not(feature = \"cjk\"))]").unwrap();
+    writeln!(out, "const {name}_LEN: usize = {len};").unwrap();
+    writeln!(
+        out,
+        "/// Autogenerated. {comment_count} sub-table(s). Consult [`lookup_width`] for layout info."
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "static {name}: Align{align}<[[u8; {size}]; {name}_LEN]> = Align{align}(["
+    )
+    .unwrap();
+    for (i, table) in tables.iter().enumerate() {
+        if i >= len {
+            out.push_str("    #[cfg(feature = \"cjk\")]\n");
+        }
+        out.push_str("    [\n");
+        byte_lines(out, table, 8);
+        out.push_str("    ],\n");
+    }
+    out.push_str("]);\n");
+}
+
+/// A sorted list of 3-byte little-endian code point ranges.
+fn range_list(out: &mut String, name: &str, ranges: &[(u32, u32)]) {
+    writeln!(
+        out,
+        "static {name}: [([u8; 3], [u8; 3]); {}] = [",
+        ranges.len()
+    )
+    .unwrap();
+    let bytes = |cp: u32| {
+        let [a, b, c, _] = cp.to_le_bytes();
+        format!("[0x{a:02X}, 0x{b:02X}, 0x{c:02X}]")
+    };
+    for &(lo, hi) in ranges {
+        writeln!(out, "    ({}, {}),", bytes(lo), bytes(hi)).unwrap();
+    }
+    out.push_str("];\n");
+}
+
+/// `set` split into 256 code point leaves keyed by the upper bits, with
+/// the ranges of lower bits in each.
+fn byte_range_leaves(set: &BTreeSet<u32>) -> BTreeMap<u32, Vec<(u32, u32)>> {
+    let mut leaves: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+    for &cp in set {
+        leaves.entry(cp >> 8).or_default().insert(cp & 0xFF);
+    }
+    leaves
+        .into_iter()
+        .map(|(top_bits, low)| (top_bits, ranges(&low)))
+        .collect()
+}
+
+fn byte_range_leaf(out: &mut String, name: &str, ranges: &[(u32, u32)]) {
+    out.push_str("#[rustfmt::skip]\n");
+    writeln!(out, "static {name}: [(u8, u8); {}] = [", ranges.len()).unwrap();
+    for (lo, hi) in ranges {
+        writeln!(out, "    (0x{lo:02X}, 0x{hi:02X}),").unwrap();
+    }
+    out.push_str("];\n");
+}
+
+/// 1024-bit bitmaps of `set`, keyed by the upper bits.
+fn bitmap_leaves(set: &BTreeSet<u32>) -> BTreeMap<u32, Vec<u8>> {
+    let mut leaves: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+    for &cp in set {
+        let leaf = leaves.entry(cp >> 10).or_insert_with(|| vec![0; 128]);
+        leaf[(cp >> 3 & 0x7F) as usize] |= 1 << (cp & 7);
+    }
+    leaves
+}
+
+/// Everything after the `Align128` definition up to the tests.
+fn data(tables: &Tables) -> io::Result<String> {
+    let packed = pack(tables)?;
+    let mut out = String::new();
+    root_table(&mut out, "WIDTH_ROOT", 1, false, &packed.root);
+    root_table(
+        &mut out,
+        "WIDTH_ROOT_CJK",
+        packed.middle_len,
+        true,
+        &packed.root_cjk,
+    );
+    out.push('\n');
+    sub_tables(
+        &mut out,
+        "WIDTH_MIDDLE",
+        64,
+        packed.middle.len() - packed.middle_len,
+        &packed.middle,
+        packed.middle_len,
+    );
+    out.push('\n');
+    sub_tables(
+        &mut out,
+        "WIDTH_LEAVES",
+        32,
+        packed.leaves.len(),
+        &packed.leaves,
+        packed.leaves_len,
+    );
+
+    out.push_str(
+        "
+/// Sorted list of codepoint ranges (inclusive)
+/// that are zero-width but not `Joining_Type=Transparent`
+/// FIXME: can we get better compression?
+",
+    );
+    range_list(
+        &mut out,
+        "NON_TRANSPARENT_ZERO_WIDTHS",
+        &ranges(&tables.non_transparent_zero_widths),
+    );
+
+    out.push_str(
+        "
+/// Sorted list of codepoint ranges (inclusive)
+/// that don't affect how the combining solidus applies
+/// (mostly ccc > 1).
+/// FIXME: can we get better compression?
+#[cfg(feature = \"cjk\")]
+",
+    );
+    // `is_solidus_transparent` checks `is_ligature_transparent` first, so
+    // ranges can be joined across those characters
+    let mut solidus = tables.solidus_transparent.clone();
+    for (lo, hi) in ranges(&tables.ligature_transparent) {
+        if lo > 0 && solidus.contains(&(lo - 1)) && solidus.contains(&(hi + 1)) {
+            solidus.extend(lo..=hi);
+        }
+    }
+    range_list(&mut out, "SOLIDUS_TRANSPARENT", &ranges(&solidus));
+
+    let emoji_leaves = bitmap_leaves(&tables.emoji_presentation_seq);
+    out.push_str(
+        "
+/// Array of 1024-bit bitmaps. Index into the correct bitmap with the 10 LSB of your codepoint
+/// to get whether it can start an emoji presentation sequence.
+",
+    );
+    writeln!(
+        out,
+        "static EMOJI_PRESENTATION_LEAVES: Align128<[[u8; 128]; {}]> = Align128([",
+        emoji_leaves.len()
+    )
+    .unwrap();
+    for leaf in emoji_leaves.values() {
+        out.push_str("    [\n");
+        byte_lines(&mut out, leaf, 8);
+        out.push_str("    ],\n");
+    }
+    out.push_str("]);\n");
+
+    let leaf_sets = [
+        ("TEXT_PRESENTATION_LEAF", &tables.text_presentation_seq),
+        ("EMOJI_MODIFIER_LEAF", &tables.emoji_modifier_base),
+    ];
+    for (name, set) in leaf_sets {
+        for (i, ranges) in byte_range_leaves(set).values().enumerate() {
+            out.push('\n');
+            byte_range_leaf(&mut out, &format!("{name}_{i}"), ranges);
+        }
+    }
+    out.push('\n');
+    Ok(out)
+}
+
+fn char_literal(cp: u32) -> String {
+    format!("'\\u{{{cp:X}}}'")
+}
+
+/// A `match` pattern for the code points in `ranges`.
+fn char_pattern(ranges: &[(u32, u32)]) -> String {
+    let patterns: Vec<_> = ranges
+        .iter()
+        .map(|&(lo, hi)| match lo == hi {
+            true => char_literal(lo),
+            false => format!("{}..={}", char_literal(lo), char_literal(hi)),
+        })
+        .collect();
+    patterns.join(" | ")
+}
+
+/// The arms of the `match` in `lookup_width` for the characters stored as
+/// `3`, other than the final emoji presentation arm. Each kind of special
+/// character gets one arm, from its first to last code point.
+fn special_arms(widths: &[Width]) -> io::Result<String> {
+    let mut bounds: BTreeMap<Special, (u32, u32)> = BTreeMap::new();
+    for (cp, width) in (0..).zip(widths) {
+        if let Width::Special(special) = *width {
+            let (_, hi) = bounds.entry(special).or_insert((cp, cp));
+            *hi = cp;
+        }
+    }
+    bounds.remove(&Special::EmojiPresentation);
+    let mut arms: Vec<(u32, u32, Special)> = bounds
+        .into_iter()
+        .map(|(special, (lo, hi))| (lo, hi, special))
+        .collect();
+    arms.sort();
+
+    // The arms are ranges, so they must not take in other special characters
+    for (cp, width) in (0..).zip(widths) {
+        if let Width::Special(special) = *width {
+            let arm = arms.iter().find(|&&(lo, hi, _)| (lo..=hi).contains(&cp));
+            if arm.map_or(Special::EmojiPresentation, |&(_, _, s)| s) != special {
+                return Err(invalid_data(format!(
+                    "U+{cp:04X} ({special:?}) falls in the match arm of another character"
+                )));
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for (lo, hi, special) in arms {
+        writeln!(
+            out,
+            "            {} => ({}, WidthInfo::{}),",
+            char_pattern(&[(lo, hi)]),
+            special.width(),
+            special.info()
+        )
+        .unwrap();
+    }
+    Ok(out)
+}
+
+/// Replaces the text between the first `start` and the next `end` after
+/// `from`, returning the end of the new text.
+fn replace_from(
+    src: &mut String,
+    from: usize,
+    start: &str,
+    end: &str,
+    with: impl FnOnce(&str) -> String,
+) -> io::Result<usize> {
+    let not_found = |what: &str| invalid_data(format!("{what:?} not found in tables.rs"));
+    let begin = from + src[from..].find(start).ok_or_else(|| not_found(start))? + start.len();
+    let finish = begin + src[begin..].find(end).ok_or_else(|| not_found(end))?;
+    let new = with(&src[begin..finish]);
+    src.replace_range(begin..finish, &new);
+    Ok(begin + new.len())
+}
+
+/// Like `replace_from`, searching from `anchor`.
+fn replace(src: &mut String, anchor: &str, start: &str, end: &str, with: &str) -> io::Result<()> {
+    let from = src
+        .find(anchor)
+        .ok_or_else(|| invalid_data(format!("{anchor:?} not found in tables.rs")))?;
+    replace_from(src, from, start, end, |_| with.to_string()).map(drop)
+}
+
+/// Writes `tables` into `src`, the contents of `tables.rs`, replacing the
+/// generated items and `match` arms and keeping everything else.
+///
+/// The output is formatted as `rustfmt` formats the current tables; a
+/// much longer character list may need a `cargo fmt` afterwards.
+pub fn splice(src: &str, tables: &Tables) -> io::Result<String> {
+    let mut out = src.to_string();
+    let (major, minor, update) = tables.version;
+    let version = format!("({major}, {minor}, {update})");
+    replace(
+        &mut out,
+        "pub const UNICODE_VERSION",
+        " = ",
+        ";\n",
+        &version,
+    )?;
+
+    let arms = special_arms(&tables.widths)?;
+    replace(
+        &mut out,
+        "fn lookup_width(c: char)",
+        "match c {\n",
+        "            _ => ",
+        &arms,
+    )?;
+    let arms = special_arms(&tables.widths_cjk)?;
+    replace(
+        &mut out,
+        "fn lookup_width_cjk(c: char)",
+        "match c {\n",
+        "            _ => ",
+        &arms,
+    )?;
+
+    let lam = char_pattern(&ranges(&tables.joining_group_lam));
+    let mut from = 0;
+    while let Some(found) = out[from..].find("// Arabic Lam-Alef ligature\n") {
+        from = replace_from(&mut out, from + found, "ALEF,\n", ",\n", |old| {
+            let indent = old.len() - old.trim_start().len();
+            format!("{}{lam}", &old[..indent])
+        })?;
+    }
+
+    let lig = char_pattern(&ranges(&tables.ligature_transparent));
+    replace(
+        &mut out,
+        "fn is_ligature_transparent(c: char)",
+        "matches!(c, ",
+        ")\n",
+        &lig,
+    )?;
+
+    let mut arms = String::new();
+    for (i, top_bits) in bitmap_leaves(&tables.emoji_presentation_seq)
+        .keys()
+        .enumerate()
+    {
+        writeln!(arms, "        0x{top_bits:X} => {i},").unwrap();
+    }
+    let end = "        _ => return false,";
+    replace(
+        &mut out,
+        "fn starts_emoji_presentation_seq(",
+        "match top_bits {\n",
+        end,
+        &arms,
+    )?;
+
+    let leaf_fns = [
+        (
+            "fn starts_non_ideographic_text_presentation_seq(",
+            "TEXT_PRESENTATION_LEAF",
+            &tables.text_presentation_seq,
+        ),
+        (
+            "fn is_emoji_modifier_base(",
+            "EMOJI_MODIFIER_LEAF",
+            &tables.emoji_modifier_base,
+        ),
+    ];
+    for (anchor, name, set) in leaf_fns {
+        let mut arms = String::new();
+        for (i, top_bits) in byte_range_leaves(set).keys().enumerate() {
+            writeln!(arms, "        0x{top_bits:X} => &{name}_{i},").unwrap();
+        }
+        replace(&mut out, anchor, "match top_bits {\n", end, &arms)?;
+    }
+
+    let data = data(tables)?;
+    let align = "struct Align128<T>(T);\n";
+    replace(&mut out, align, align, "#[cfg(test)]\nmod tests", &data)?;
+    Ok(out)
+}
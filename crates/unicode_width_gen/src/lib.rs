@@ -0,0 +1,30 @@
+//! Regenerates the lookup tables in `unicode_width/src/tables.rs` from the
+//! [Unicode Character Database](https://www.unicode.org/ucd/).
+//!
+//! Generation happens in three steps:
+//!
+//! 1. [`Ucd::load`] reads the data files from an unzipped copy of `UCD.zip`.
+//! 2. [`Ucd::tables`] applies the rules from the "Rules for determining
+//!    width" section of the `unicode_width` documentation, giving the width
+//!    of every code point and the character sets used by the string width
+//!    state machine, as [`Tables`].
+//! 3. [`splice`] packs [`Tables`] into the multi-level lookup tables and
+//!    writes them, and the `match` arms that depend on them, into the
+//!    existing `tables.rs`. The hand-written code around them is kept.
+//!
+//! Packing is deterministic, so splicing the tables decoded from a
+//! `tables.rs` back into it reproduces the file byte for byte.
+
+use std::io;
+
+mod emit;
+mod tables;
+mod ucd;
+
+pub use emit::splice;
+pub use tables::{Special, Tables, Width, NUM_CODEPOINTS};
+pub use ucd::{Ucd, UCD_FILES};
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
@@ -0,0 +1,66 @@
+use std::{env, fs, path::PathBuf, process};
+
+use unicode_width_gen::{splice, Ucd, UCD_FILES};
+
+const USAGE: &str = "\
+Usage: unicode_width_gen [--check] UCD_DIR [TABLES_RS]
+
+Regenerates the lookup tables in TABLES_RS (by default the unicode_width
+crate's src/tables.rs) from UCD_DIR, an unzipped UCD.zip from
+https://www.unicode.org/Public/<version>/ucd/.
+
+    --check    Don't write anything, exit with 1 if TABLES_RS is out of date";
+
+fn main() {
+    let mut check = false;
+    let mut paths = Vec::new();
+    for arg in env::args_os().skip(1) {
+        match arg.to_str() {
+            Some("--check") => check = true,
+            Some("-h" | "--help") => {
+                println!("{USAGE}");
+                return;
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    let (ucd_dir, tables_rs) = match paths.as_slice() {
+        [ucd_dir] => (
+            ucd_dir.clone(),
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../unicode_width/src/tables.rs"),
+        ),
+        [ucd_dir, tables_rs] => (ucd_dir.clone(), tables_rs.clone()),
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    let result = Ucd::load(&ucd_dir).and_then(|ucd| {
+        let old = fs::read_to_string(&tables_rs)?;
+        let new = splice(&old, &ucd.tables())?;
+        Ok((ucd.version(), old, new))
+    });
+    let (version, old, new) = match result {
+        Ok(generated) => generated,
+        Err(err) => {
+            eprintln!("unicode_width_gen: {err}");
+            eprintln!("UCD_DIR must contain {}", UCD_FILES.join(", "));
+            process::exit(2);
+        }
+    };
+
+    let (major, minor, update) = version;
+    let path = tables_rs.display();
+    if old == new {
+        println!("{path} is up to date with Unicode {major}.{minor}.{update}");
+    } else if check {
+        eprintln!("{path} is out of date with Unicode {major}.{minor}.{update}");
+        process::exit(1);
+    } else if let Err(err) = fs::write(&tables_rs, new) {
+        eprintln!("unicode_width_gen: {path}: {err}");
+        process::exit(2);
+    } else {
+        println!("Wrote {path} for Unicode {major}.{minor}.{update}");
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeSet;
+
+/// Number of code points, `U+0000` to `U+10FFFF`.
+pub const NUM_CODEPOINTS: usize = 0x110000;
+
+/// A character whose width depends on the characters around it. The lookup
+/// tables store `3` for these, and `lookup_width` resolves them with a
+/// `match` on the character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Special {
+    /// `'\n'`, which forms `"\r\n"`.
+    LineFeed,
+    /// `'\u{338}'`, which widens `<`, `=` and `>` in an East Asian context.
+    CombiningLongSolidusOverlay,
+    /// `'\u{5DC}'`, the end of the Hebrew Alef-Lamed ligature.
+    HebrewLetterLamed,
+    /// Characters with `Joining_Group=Alef`, the end of the Lam-Alef
+    /// ligature.
+    JoiningGroupAlef,
+    /// Khmer letters that form coeng signs after `'\u{17D2}'`.
+    KhmerCoengEligibleLetter,
+    /// `'\u{17D8}'` KHMER SIGN BEYYAL, 3 columns wide.
+    KhmerSignBeyyal,
+    /// `'\u{1A10}'`, the end of the Buginese `<a, -i> ya` ligature.
+    BugineseLetterYa,
+    /// Tifinagh consonants, which join with `'\u{2D7F}'`.
+    TifinaghConsonant,
+    /// `'\u{A4FC}'` and `'\u{A4FD}'`, which end Lisu tone letter
+    /// combinations.
+    LisuToneLetterMyaNaJeu,
+    /// `'\u{FE0E}'`, which selects text presentation.
+    VariationSelector15,
+    /// `'\u{FE0F}'`, which selects emoji presentation.
+    VariationSelector16,
+    /// `'\u{10C03}'`, the end of the Old Turkic ligature.
+    OldTurkicLetterOrkhonI,
+    /// Regional indicators, which pair up into flags.
+    RegionalIndicator,
+    /// Characters with `Emoji_Modifier`.
+    EmojiModifier,
+    /// Other characters with `Emoji_Presentation`, which can start emoji
+    /// ZWJ sequences.
+    EmojiPresentation,
+}
+
+impl Special {
+    /// All the variants.
+    pub const ALL: [Special; 15] = [
+        Special::LineFeed,
+        Special::CombiningLongSolidusOverlay,
+        Special::HebrewLetterLamed,
+        Special::JoiningGroupAlef,
+        Special::KhmerCoengEligibleLetter,
+        Special::KhmerSignBeyyal,
+        Special::BugineseLetterYa,
+        Special::TifinaghConsonant,
+        Special::LisuToneLetterMyaNaJeu,
+        Special::VariationSelector15,
+        Special::VariationSelector16,
+        Special::OldTurkicLetterOrkhonI,
+        Special::RegionalIndicator,
+        Special::EmojiModifier,
+        Special::EmojiPresentation,
+    ];
+
+    /// The width `lookup_width` returns for the character.
+    pub fn width(self) -> u8 {
+        match self {
+            Special::CombiningLongSolidusOverlay
+            | Special::VariationSelector15
+            | Special::VariationSelector16 => 0,
+            Special::EmojiModifier | Special::EmojiPresentation => 2,
+            Special::KhmerSignBeyyal => 3,
+            _ => 1,
+        }
+    }
+
+    /// The name of the `WidthInfo` constant `lookup_width` returns for the
+    /// character.
+    pub fn info(self) -> &'static str {
+        match self {
+            Special::LineFeed => "LINE_FEED",
+            Special::CombiningLongSolidusOverlay => "COMBINING_LONG_SOLIDUS_OVERLAY",
+            Special::HebrewLetterLamed => "HEBREW_LETTER_LAMED",
+            Special::JoiningGroupAlef => "JOINING_GROUP_ALEF",
+            Special::KhmerCoengEligibleLetter => "KHMER_COENG_ELIGIBLE_LETTER",
+            Special::KhmerSignBeyyal => "DEFAULT",
+            Special::BugineseLetterYa => "BUGINESE_LETTER_YA",
+            Special::TifinaghConsonant => "TIFINAGH_CONSONANT",
+            Special::LisuToneLetterMyaNaJeu => "LISU_TONE_LETTER_MYA_NA_JEU",
+            Special::VariationSelector15 => "VARIATION_SELECTOR_15",
+            Special::VariationSelector16 => "VARIATION_SELECTOR_16",
+            Special::OldTurkicLetterOrkhonI => "OLD_TURKIC_LETTER_ORKHON_I",
+            Special::RegionalIndicator => "REGIONAL_INDICATOR",
+            Special::EmojiModifier => "EMOJI_MODIFIER",
+            Special::EmojiPresentation => "EMOJI_PRESENTATION",
+        }
+    }
+}
+
+/// The width of a single code point, as stored in the lookup tables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Width {
+    /// 0 columns.
+    Zero,
+    /// 1 column.
+    Narrow,
+    /// 2 columns.
+    Wide,
+    /// Depends on the surrounding characters.
+    Special(Special),
+}
+
+impl Width {
+    /// The 2-bit value stored in `WIDTH_LEAVES`.
+    pub fn code(self) -> u8 {
+        match self {
+            Width::Zero => 0,
+            Width::Narrow => 1,
+            Width::Wide => 2,
+            Width::Special(_) => 3,
+        }
+    }
+
+    /// The width `lookup_width` returns for the character.
+    pub fn columns(self) -> u8 {
+        match self {
+            Width::Special(special) => special.width(),
+            width => width.code(),
+        }
+    }
+}
+
+/// Everything in `tables.rs` that is generated from the UCD.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tables {
+    /// `UNICODE_VERSION`.
+    pub version: (u8, u8, u8),
+    /// Width of each code point, ambiguous characters being narrow.
+    pub widths: Vec<Width>,
+    /// Width of each code point, ambiguous characters being wide.
+    pub widths_cjk: Vec<Width>,
+    /// Characters with `Joining_Group=Lam`, which start the Lam-Alef
+    /// ligature.
+    pub joining_group_lam: BTreeSet<u32>,
+    /// Default-ignorable combining marks and ZWJ (`is_ligature_transparent`).
+    pub ligature_transparent: BTreeSet<u32>,
+    /// Zero width characters without `Joining_Type=Transparent`
+    /// (`NON_TRANSPARENT_ZERO_WIDTHS`).
+    pub non_transparent_zero_widths: BTreeSet<u32>,
+    /// Characters that can come between a base character and `'\u{338}'`,
+    /// other than the ligature transparent ones (`SOLIDUS_TRANSPARENT`).
+    pub solidus_transparent: BTreeSet<u32>,
+    /// Characters that start an emoji presentation sequence
+    /// (`EMOJI_PRESENTATION_LEAVES`).
+    pub emoji_presentation_seq: BTreeSet<u32>,
+    /// `Emoji_Presentation` characters that start a text presentation
+    /// sequence and are not ideographic (`TEXT_PRESENTATION_LEAF_*`).
+    pub text_presentation_seq: BTreeSet<u32>,
+    /// Characters with `Emoji_Modifier_Base` (`EMOJI_MODIFIER_LEAF_*`).
+    pub emoji_modifier_base: BTreeSet<u32>,
+}
+
+/// The inclusive ranges of consecutive code points in `set`.
+pub(crate) fn ranges(set: &BTreeSet<u32>) -> Vec<(u32, u32)> {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &cp in set {
+        match ranges.last_mut() {
+            Some((_, hi)) if *hi + 1 == cp => *hi = cp,
+            _ => ranges.push((cp, cp)),
+        }
+    }
+    ranges
+}
@@ -0,0 +1,396 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs, io,
+    path::Path,
+};
+
+use crate::{
+    invalid_data,
+    tables::{Special, Tables, Width, NUM_CODEPOINTS},
+};
+
+/// The files read from the UCD directory, relative to it.
+pub const UCD_FILES: [&str; 11] = [
+    "UnicodeData.txt",
+    "EastAsianWidth.txt",
+    "DerivedCoreProperties.txt",
+    "PropList.txt",
+    "HangulSyllableType.txt",
+    "LineBreak.txt",
+    "ArabicShaping.txt",
+    "auxiliary/GraphemeBreakProperty.txt",
+    "extracted/DerivedJoiningType.txt",
+    "emoji/emoji-data.txt",
+    "emoji/emoji-variation-sequences.txt",
+];
+
+/// Khmer letters that form coeng signs after `'\u{17D2}'`.
+const KHMER_COENG_ELIGIBLE_LETTERS: [(u32, u32); 11] = [
+    (0x1780, 0x1782),
+    (0x1784, 0x1787),
+    (0x1789, 0x178C),
+    (0x178E, 0x1793),
+    (0x1795, 0x1798),
+    (0x179B, 0x179D),
+    (0x17A0, 0x17A0),
+    (0x17A2, 0x17A2),
+    (0x17A7, 0x17A7),
+    (0x17AB, 0x17AC),
+    (0x17AF, 0x17AF),
+];
+
+/// `Prepended_Concatenation_Mark`s that are written above the number they
+/// apply to, and so take up no columns of their own.
+const ZERO_WIDTH_CONCATENATION_MARKS: [u32; 5] = [0x605, 0x70F, 0x890, 0x891, 0x8E2];
+
+/// East Asian Width, reduced to what matters for the tables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Eaw {
+    Narrow,
+    Ambiguous,
+    Wide,
+}
+
+/// The properties of every code point needed to build [`Tables`].
+#[derive(Clone, Debug)]
+pub struct Ucd {
+    version: (u8, u8, u8),
+    general_category: Vec<[u8; 2]>,
+    combining_class: Vec<u8>,
+    decomposition: HashMap<u32, Vec<u32>>,
+    east_asian_width: Vec<Eaw>,
+    default_ignorable: BTreeSet<u32>,
+    grapheme_extend: BTreeSet<u32>,
+    concatenation_mark: BTreeSet<u32>,
+    regional_indicator: BTreeSet<u32>,
+    prepend: BTreeSet<u32>,
+    hangul_vowel_or_trailing: BTreeSet<u32>,
+    line_break_ai: BTreeSet<u32>,
+    joining_group_alef: BTreeSet<u32>,
+    joining_group_lam: BTreeSet<u32>,
+    joining_transparent: BTreeSet<u32>,
+    emoji_presentation: BTreeSet<u32>,
+    emoji_modifier: BTreeSet<u32>,
+    emoji_modifier_base: BTreeSet<u32>,
+    emoji_style: BTreeSet<u32>,
+    text_style: BTreeSet<u32>,
+}
+
+/// The lines of a UCD data file split at `;`, without comments and blank
+/// lines.
+fn records(text: &str) -> impl Iterator<Item = Vec<&str>> {
+    text.lines().filter_map(|line| {
+        let data = line.split('#').next().unwrap_or("").trim();
+        (!data.is_empty()).then(|| data.split(';').map(str::trim).collect())
+    })
+}
+
+fn codepoint(s: &str) -> io::Result<u32> {
+    u32::from_str_radix(s, 16)
+        .ok()
+        .filter(|&cp| (cp as usize) < NUM_CODEPOINTS)
+        .ok_or_else(|| invalid_data(format!("bad code point {s:?}")))
+}
+
+/// Parses `XXXX` or `XXXX..YYYY`.
+fn codepoints(s: &str) -> io::Result<(u32, u32)> {
+    match s.split_once("..") {
+        Some((lo, hi)) => Ok((codepoint(lo)?, codepoint(hi)?)),
+        None => codepoint(s).map(|cp| (cp, cp)),
+    }
+}
+
+/// The code points whose property in the second field is `value`.
+fn property(text: &str, value: &str) -> io::Result<BTreeSet<u32>> {
+    let mut set = BTreeSet::new();
+    for fields in records(text) {
+        if fields.get(1) == Some(&value) {
+            let (lo, hi) = codepoints(fields[0])?;
+            set.extend(lo..=hi);
+        }
+    }
+    Ok(set)
+}
+
+/// The version in the first line of a data file, such as
+/// `# DerivedCoreProperties-15.1.0.txt`.
+fn parse_version(text: &str) -> io::Result<(u8, u8, u8)> {
+    let first = text.lines().next().unwrap_or("");
+    let version = first
+        .rsplit_once('-')
+        .and_then(|(_, rest)| rest.strip_suffix(".txt"))
+        .map(|version| version.split('.').map(str::parse).collect::<Vec<_>>());
+    match version.as_deref() {
+        Some(&[Ok(major), Ok(minor), Ok(update)]) => Ok((major, minor, update)),
+        _ => Err(invalid_data(format!("no Unicode version in {first:?}"))),
+    }
+}
+
+fn parse_east_asian_width(text: &str) -> io::Result<Vec<Eaw>> {
+    let eaw = |value: &str| match value {
+        "W" | "F" => Eaw::Wide,
+        "A" => Eaw::Ambiguous,
+        _ => Eaw::Narrow,
+    };
+    let mut widths = vec![Eaw::Narrow; NUM_CODEPOINTS];
+    // Unassigned code points take their width from the `@missing` lines,
+    // such as those for the CJK ideograph blocks.
+    for line in text.lines() {
+        if let Some(missing) = line.strip_prefix("# @missing:") {
+            let mut fields = missing.split(';').map(str::trim);
+            let (lo, hi) = codepoints(fields.next().unwrap_or(""))?;
+            let width = eaw(fields.next().unwrap_or(""));
+            widths[lo as usize..=hi as usize].fill(width);
+        }
+    }
+    for fields in records(text) {
+        let (lo, hi) = codepoints(fields[0])?;
+        let width = eaw(fields.get(1).copied().unwrap_or(""));
+        widths[lo as usize..=hi as usize].fill(width);
+    }
+    Ok(widths)
+}
+
+impl Ucd {
+    /// Reads the [`UCD_FILES`] from `dir`, an unzipped `UCD.zip`.
+    pub fn load(dir: &Path) -> io::Result<Ucd> {
+        Ucd::from_files(|name| {
+            fs::read_to_string(dir.join(name))
+                .map_err(|err| io::Error::new(err.kind(), format!("{name}: {err}")))
+        })
+    }
+
+    /// Builds the database from the [`UCD_FILES`], returned by `read`.
+    pub fn from_files(read: impl Fn(&str) -> io::Result<String>) -> io::Result<Ucd> {
+        let unicode_data = read("UnicodeData.txt")?;
+        let mut general_category = vec![*b"Cn"; NUM_CODEPOINTS];
+        let mut combining_class = vec![0; NUM_CODEPOINTS];
+        let mut decomposition = HashMap::new();
+        let mut range_start = None;
+        for fields in records(&unicode_data) {
+            if fields.len() < 6 {
+                return Err(invalid_data(format!("short UnicodeData line {fields:?}")));
+            }
+            let cp = codepoint(fields[0])?;
+            let gc: [u8; 2] = fields[2]
+                .as_bytes()
+                .try_into()
+                .map_err(|_| invalid_data(format!("bad category {:?}", fields[2])))?;
+            let ccc = fields[3]
+                .parse()
+                .map_err(|_| invalid_data(format!("bad combining class {:?}", fields[3])))?;
+            // Large blocks are given as a `<..., First>` and `<..., Last>` pair
+            let lo = if fields[1].ends_with(", Last>") {
+                range_start.take().unwrap_or(cp)
+            } else {
+                cp
+            };
+            if fields[1].ends_with(", First>") {
+                range_start = Some(cp);
+            }
+            general_category[lo as usize..=cp as usize].fill(gc);
+            combining_class[lo as usize..=cp as usize].fill(ccc);
+            // Compatibility decompositions start with a `<tag>`
+            if !fields[5].is_empty() && !fields[5].starts_with('<') {
+                let mapping = fields[5]
+                    .split_whitespace()
+                    .map(codepoint)
+                    .collect::<io::Result<_>>()?;
+                decomposition.insert(cp, mapping);
+            }
+        }
+
+        let derived_core = read("DerivedCoreProperties.txt")?;
+        let prop_list = read("PropList.txt")?;
+        let emoji_data = read("emoji/emoji-data.txt")?;
+        let hangul = read("HangulSyllableType.txt")?;
+        let mut hangul_vowel_or_trailing = property(&hangul, "V")?;
+        hangul_vowel_or_trailing.append(&mut property(&hangul, "T")?);
+
+        let mut joining_group_alef = BTreeSet::new();
+        let mut joining_group_lam = BTreeSet::new();
+        for fields in records(&read("ArabicShaping.txt")?) {
+            match fields.get(3) {
+                Some(&"ALEF") => joining_group_alef.insert(codepoint(fields[0])?),
+                Some(&"LAM") => joining_group_lam.insert(codepoint(fields[0])?),
+                _ => false,
+            };
+        }
+
+        let mut emoji_style = BTreeSet::new();
+        let mut text_style = BTreeSet::new();
+        for fields in records(&read("emoji/emoji-variation-sequences.txt")?) {
+            let mut sequence = fields[0].split_whitespace();
+            let base = codepoint(sequence.next().unwrap_or(""))?;
+            match (sequence.next(), fields.get(1)) {
+                (Some("FE0F"), Some(&"emoji style")) => emoji_style.insert(base),
+                (Some("FE0E"), Some(&"text style")) => text_style.insert(base),
+                _ => return Err(invalid_data(format!("bad variation sequence {fields:?}"))),
+            };
+        }
+
+        Ok(Ucd {
+            version: parse_version(&derived_core)?,
+            general_category,
+            combining_class,
+            decomposition,
+            east_asian_width: parse_east_asian_width(&read("EastAsianWidth.txt")?)?,
+            default_ignorable: property(&derived_core, "Default_Ignorable_Code_Point")?,
+            grapheme_extend: property(&derived_core, "Grapheme_Extend")?,
+            concatenation_mark: property(&prop_list, "Prepended_Concatenation_Mark")?,
+            regional_indicator: property(&prop_list, "Regional_Indicator")?,
+            prepend: property(&read("auxiliary/GraphemeBreakProperty.txt")?, "Prepend")?,
+            hangul_vowel_or_trailing,
+            line_break_ai: property(&read("LineBreak.txt")?, "AI")?,
+            joining_group_alef,
+            joining_group_lam,
+            joining_transparent: property(&read("extracted/DerivedJoiningType.txt")?, "T")?,
+            emoji_presentation: property(&emoji_data, "Emoji_Presentation")?,
+            emoji_modifier: property(&emoji_data, "Emoji_Modifier")?,
+            emoji_modifier_base: property(&emoji_data, "Emoji_Modifier_Base")?,
+            emoji_style,
+            text_style,
+        })
+    }
+
+    /// The Unicode version of the data.
+    pub fn version(&self) -> (u8, u8, u8) {
+        self.version
+    }
+
+    fn is_mark(&self, cp: u32) -> bool {
+        self.general_category[cp as usize][0] == b'M'
+    }
+
+    /// The full canonical decomposition of `cp`.
+    fn nfd(&self, cp: u32) -> Vec<u32> {
+        match self.decomposition.get(&cp) {
+            Some(mapping) => mapping.iter().flat_map(|&c| self.nfd(c)).collect(),
+            None => vec![cp],
+        }
+    }
+
+    /// Characters that are part of ligatures or emoji sequences, which the
+    /// `match` in `lookup_width` handles.
+    fn special(&self, cp: u32, cjk: bool) -> Option<Special> {
+        let in_ranges =
+            |ranges: &[(u32, u32)]| ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&cp));
+        let special = match cp {
+            0xA => Special::LineFeed,
+            0x338 if cjk => Special::CombiningLongSolidusOverlay,
+            0x5DC => Special::HebrewLetterLamed,
+            _ if self.joining_group_alef.contains(&cp) => Special::JoiningGroupAlef,
+            _ if in_ranges(&KHMER_COENG_ELIGIBLE_LETTERS) => Special::KhmerCoengEligibleLetter,
+            0x17D8 => Special::KhmerSignBeyyal,
+            0x1A10 => Special::BugineseLetterYa,
+            0x2D31..=0x2D65 | 0x2D6F => Special::TifinaghConsonant,
+            0xA4FC..=0xA4FD => Special::LisuToneLetterMyaNaJeu,
+            0xFE0E if !cjk => Special::VariationSelector15,
+            0xFE0F => Special::VariationSelector16,
+            0x10C03 => Special::OldTurkicLetterOrkhonI,
+            _ if self.regional_indicator.contains(&cp) => Special::RegionalIndicator,
+            _ if self.emoji_modifier.contains(&cp) => Special::EmojiModifier,
+            _ if self.emoji_presentation.contains(&cp) => Special::EmojiPresentation,
+            _ => return None,
+        };
+        Some(special)
+    }
+
+    /// Whether `cp` is zero width by the rules for single characters.
+    fn is_zero_width(&self, cp: u32) -> bool {
+        let nfd = self.nfd(cp);
+        self.default_ignorable.contains(&cp)
+            || self.grapheme_extend.contains(&cp)
+            // Such as the Kannada and Balinese vowel signs made of two
+            // `Grapheme_Extend` characters
+            || nfd.len() > 1 && nfd.iter().all(|c| self.grapheme_extend.contains(c))
+            || self.hangul_vowel_or_trailing.contains(&cp)
+            || ZERO_WIDTH_CONCATENATION_MARKS.contains(&cp)
+            || self.prepend.contains(&cp) && !self.concatenation_mark.contains(&cp)
+            || cp == 0xA8FA
+    }
+
+    fn is_ambiguous(&self, cp: u32) -> bool {
+        let gc = self.general_category[cp as usize];
+        let ambiguous = self.east_asian_width[cp as usize] == Eaw::Ambiguous
+            || self.line_break_ai.contains(&cp)
+            || cp == 0x387
+            || matches!(
+                self.decomposition.get(&cp).map(Vec::as_slice),
+                Some(&[base, 0x338]) if self.east_asian_width[base as usize] == Eaw::Ambiguous
+            );
+        ambiguous && gc[0] != b'L' && gc != *b"Sk"
+    }
+
+    /// The width of `cp`, following the "Rules for determining width" in
+    /// the `unicode_width` documentation.
+    fn width(&self, cp: u32, cjk: bool) -> Width {
+        if let Some(special) = self.special(cp, cjk) {
+            return Width::Special(special);
+        }
+        match cp {
+            0x2D7F => Width::Narrow,
+            0x115F | 0x17A4 => Width::Wide,
+            _ if self.is_zero_width(cp) => Width::Zero,
+            _ if self.east_asian_width[cp as usize] == Eaw::Wide => Width::Wide,
+            _ if cjk && self.is_ambiguous(cp) => Width::Wide,
+            _ => Width::Narrow,
+        }
+    }
+
+    /// Whether `cp` can come between a base character and `'\u{338}'`: its
+    /// canonical decomposition consists of characters with
+    /// `Canonical_Combining_Class` greater than 1 and default-ignorable
+    /// combining marks.
+    fn is_solidus_transparent(&self, cp: u32) -> bool {
+        self.nfd(cp).iter().all(|&c| {
+            self.combining_class[c as usize] > 1
+                || self.default_ignorable.contains(&c) && self.is_mark(c)
+        })
+    }
+
+    /// Applies the width rules to every code point.
+    pub fn tables(&self) -> Tables {
+        let all = 0..NUM_CODEPOINTS as u32;
+        let widths: Vec<Width> = all.clone().map(|cp| self.width(cp, false)).collect();
+        let widths_cjk = all.clone().map(|cp| self.width(cp, true)).collect();
+
+        let mut ligature_transparent: BTreeSet<u32> = self
+            .default_ignorable
+            .iter()
+            .copied()
+            .filter(|&cp| self.is_mark(cp))
+            .collect();
+        ligature_transparent.insert(0x200D);
+
+        let non_transparent_zero_widths = all
+            .clone()
+            .filter(|&cp| {
+                widths[cp as usize].columns() == 0 && !self.joining_transparent.contains(&cp)
+            })
+            .collect();
+        let solidus_transparent = all
+            .filter(|&cp| !ligature_transparent.contains(&cp) && self.is_solidus_transparent(cp))
+            .collect();
+        let text_presentation_seq = self
+            .text_style
+            .iter()
+            .copied()
+            // Outside the Enclosed Ideographic Supplement block
+            .filter(|cp| self.emoji_presentation.contains(cp) && !(0x1F200..0x1F300).contains(cp))
+            .collect();
+
+        Tables {
+            version: self.version,
+            widths,
+            widths_cjk,
+            joining_group_lam: self.joining_group_lam.clone(),
+            ligature_transparent,
+            non_transparent_zero_widths,
+            solidus_transparent,
+            emoji_presentation_seq: self.emoji_style.clone(),
+            text_presentation_seq,
+            emoji_modifier_base: self.emoji_modifier_base.clone(),
+        }
+    }
+}
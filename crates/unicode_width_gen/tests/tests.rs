@@ -0,0 +1,348 @@
+use std::{collections::BTreeSet, env, fs, io, path::Path};
+
+use unicode_width_gen::{splice, Special, Tables, Ucd, Width, NUM_CODEPOINTS};
+
+const TABLES_RS: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../unicode_width/src/tables.rs"
+);
+
+/// The text of the item starting with `header`, up to its closing bracket.
+fn item<'a>(src: &'a str, header: &str) -> &'a str {
+    let start = src
+        .find(header)
+        .unwrap_or_else(|| panic!("{header} not found"));
+    let end = start + src[start..].find("\n]").expect("unterminated item");
+    &src[start..end]
+}
+
+/// The `0x..` literals in `text`.
+fn hex_literals(text: &str) -> Vec<u32> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter_map(|token| token.strip_prefix("0x"))
+        .map(|digits| u32::from_str_radix(digits, 16).unwrap())
+        .collect()
+}
+
+/// The sub-tables of a nested array item.
+fn sub_tables(src: &str, header: &str) -> Vec<Vec<u8>> {
+    let body = item(src, header);
+    let body = &body[body.find("([").unwrap() + 2..];
+    body.split("    [\n")
+        .skip(1)
+        .map(|table| hex_literals(table).into_iter().map(|b| b as u8).collect())
+        .collect()
+}
+
+fn parse_char(literal: &str) -> u32 {
+    let digits = literal.trim().trim_start_matches("'\\u{");
+    u32::from_str_radix(digits.trim_end_matches("}'"), 16).unwrap()
+}
+
+/// The code points matched by a `'\u{..}' | '\u{..}'..='\u{..}'` pattern.
+fn parse_pattern(pattern: &str) -> BTreeSet<u32> {
+    let mut set = BTreeSet::new();
+    for alternative in pattern.split(" | ") {
+        match alternative.split_once("..=") {
+            Some((lo, hi)) => set.extend(parse_char(lo)..=parse_char(hi)),
+            None => {
+                set.insert(parse_char(alternative));
+            }
+        }
+    }
+    set
+}
+
+/// The lines between the `match` after `anchor` and its catch-all arm.
+fn match_arms<'a>(src: &'a str, anchor: &str, start: &str) -> Vec<&'a str> {
+    let from = src.find(anchor).unwrap();
+    let begin = from + src[from..].find(start).unwrap() + start.len();
+    let end = begin + src[begin..].find("_ =>").unwrap();
+    src[begin..end]
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .collect()
+}
+
+fn decode_widths(src: &str, root: &str, lookup: &str) -> Vec<Width> {
+    let root = hex_literals(item(src, root));
+    let middle = sub_tables(src, "static WIDTH_MIDDLE:");
+    let leaves = sub_tables(src, "static WIDTH_LEAVES:");
+    let arms: Vec<(BTreeSet<u32>, Special)> = match_arms(src, lookup, "match c {\n")
+        .into_iter()
+        .map(|arm| {
+            let (pattern, result) = arm.split_once(" => ").unwrap();
+            let special = Special::ALL
+                .into_iter()
+                .find(|s| result == format!("({}, WidthInfo::{}),", s.width(), s.info()))
+                .unwrap_or_else(|| panic!("unknown arm {arm}"));
+            (parse_pattern(pattern), special)
+        })
+        .collect();
+    (0..NUM_CODEPOINTS as u32)
+        .map(|cp| {
+            let middle = &middle[root[(cp >> 13) as usize] as usize];
+            let leaf = &leaves[middle[(cp >> 7 & 0x3F) as usize] as usize];
+            match leaf[(cp >> 2 & 0x1F) as usize] >> (2 * (cp & 3)) & 3 {
+                0 => Width::Zero,
+                1 => Width::Narrow,
+                2 => Width::Wide,
+                _ => Width::Special(
+                    arms.iter()
+                        .find(|(set, _)| set.contains(&cp))
+                        .map_or(Special::EmojiPresentation, |&(_, special)| special),
+                ),
+            }
+        })
+        .collect()
+}
+
+fn decode_ranges(src: &str, header: &str) -> BTreeSet<u32> {
+    let bytes = hex_literals(item(src, header));
+    bytes
+        .chunks(6)
+        .flat_map(|r| (r[0] | r[1] << 8 | r[2] << 16)..=(r[3] | r[4] << 8 | r[5] << 16))
+        .collect()
+}
+
+/// The code points in the leaves a `match top_bits` selects.
+fn decode_byte_range_leaves(src: &str, anchor: &str) -> BTreeSet<u32> {
+    let mut set = BTreeSet::new();
+    for arm in match_arms(src, anchor, "match top_bits {\n") {
+        let (top_bits, leaf) = arm.trim().split_once(" => &").unwrap();
+        let top_bits = hex_literals(top_bits)[0];
+        let header = format!("static {}:", leaf.trim_end_matches(','));
+        let bytes = hex_literals(item(src, &header));
+        for range in bytes.chunks(2) {
+            set.extend((range[0]..=range[1]).map(|low| top_bits << 8 | low));
+        }
+    }
+    set
+}
+
+/// Reads the generated parts of `tables.rs` back.
+fn decode(src: &str) -> Tables {
+    let version = item(src, "pub const UNICODE_VERSION");
+    let version = version[version.find(" = (").unwrap() + 4..version.find(");").unwrap()]
+        .split(", ")
+        .map(|n| n.parse().unwrap())
+        .collect::<Vec<u8>>();
+
+    let lam = item(src, "// Arabic Lam-Alef ligature\n");
+    let lam = lam.lines().nth(3).unwrap().trim().trim_end_matches(',');
+    let lig = item(src, "fn is_ligature_transparent(c: char)");
+    let lig = &lig[lig.find("matches!(c, ").unwrap() + 12..lig.find(")\n").unwrap()];
+
+    let mut emoji_presentation_seq = BTreeSet::new();
+    let emoji_leaves = sub_tables(src, "static EMOJI_PRESENTATION_LEAVES:");
+    let emoji_arms = match_arms(
+        src,
+        "fn starts_emoji_presentation_seq(",
+        "match top_bits {\n",
+    );
+    for (arm, leaf) in emoji_arms.iter().zip(&emoji_leaves) {
+        let top_bits = hex_literals(arm)[0];
+        for cp in top_bits << 10..(top_bits + 1) << 10 {
+            if leaf[(cp >> 3 & 0x7F) as usize] >> (cp & 7) & 1 == 1 {
+                emoji_presentation_seq.insert(cp);
+            }
+        }
+    }
+
+    Tables {
+        version: (version[0], version[1], version[2]),
+        widths: decode_widths(src, "static WIDTH_ROOT:", "fn lookup_width(c: char)"),
+        widths_cjk: decode_widths(
+            src,
+            "static WIDTH_ROOT_CJK:",
+            "fn lookup_width_cjk(c: char)",
+        ),
+        joining_group_lam: parse_pattern(lam),
+        ligature_transparent: parse_pattern(lig),
+        non_transparent_zero_widths: decode_ranges(src, "static NON_TRANSPARENT_ZERO_WIDTHS:"),
+        solidus_transparent: decode_ranges(src, "static SOLIDUS_TRANSPARENT:"),
+        emoji_presentation_seq,
+        text_presentation_seq: decode_byte_range_leaves(
+            src,
+            "fn starts_non_ideographic_text_presentation_seq(",
+        ),
+        emoji_modifier_base: decode_byte_range_leaves(src, "fn is_emoji_modifier_base("),
+    }
+}
+
+/// Fails with the first line where `spliced` differs from `src`.
+fn assert_unchanged(src: &str, spliced: &str) {
+    if spliced != src {
+        let line = src
+            .lines()
+            .zip(spliced.lines())
+            .position(|(a, b)| a != b)
+            .unwrap_or(src.lines().count());
+        panic!("tables.rs differs from line {}", line + 1);
+    }
+}
+
+#[test]
+fn test_splice_reproduces_tables() {
+    let src = fs::read_to_string(TABLES_RS).unwrap();
+    let tables = decode(&src);
+    assert_eq!(tables.version, (15, 1, 0));
+    assert_eq!(tables.widths['a' as usize], Width::Narrow);
+    assert_eq!(tables.widths['\u{2081}' as usize], Width::Narrow);
+    assert_eq!(tables.widths_cjk['\u{2081}' as usize], Width::Wide);
+    assert_eq!(
+        tables.widths['\u{1F600}' as usize],
+        Width::Special(Special::EmojiPresentation)
+    );
+
+    assert_unchanged(&src, &splice(&src, &tables).unwrap());
+
+    // The data is written from the tables, not kept
+    let align = "struct Align128<T>(T);\n";
+    let start = src.find(align).unwrap() + align.len();
+    let end = src.find("#[cfg(test)]\nmod tests").unwrap();
+    let emptied = format!("{}{}", &src[..start], &src[end..]);
+    assert!(splice(&emptied, &tables).unwrap() == src);
+}
+
+#[test]
+fn test_splice_version() {
+    let src = fs::read_to_string(TABLES_RS).unwrap();
+    let mut tables = decode(&src);
+    tables.version = (16, 0, 0);
+    let spliced = splice(&src, &tables).unwrap();
+    assert!(spliced.contains("pub const UNICODE_VERSION: (u8, u8, u8) = (16, 0, 0);\n"));
+    assert_eq!(spliced.len(), src.len());
+}
+
+#[test]
+fn test_splice_overlapping_arms() {
+    let src = fs::read_to_string(TABLES_RS).unwrap();
+    let mut tables = decode(&src);
+    // A Hebrew Lamed inside the Alef range can't be told apart by the match
+    tables.widths[0x700] = Width::Special(Special::HebrewLetterLamed);
+    let err = splice(&src, &tables).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+/// A tiny UCD, in the format of the real files.
+fn ucd_file(name: &str) -> io::Result<String> {
+    let text = match name {
+        "UnicodeData.txt" => {
+            "\
+000A;<control>;Cc;0;B;;;;;N;LINE FEED (LF);;;;
+0041;LATIN CAPITAL LETTER A;Lu;0;L;;;;;N;;;;0061;
+00A1;INVERTED EXCLAMATION MARK;Po;0;ON;;;;;N;;;;;
+0300;COMBINING GRAVE ACCENT;Mn;230;NSM;;;;;N;NON-SPACING GRAVE;;;;
+034F;COMBINING GRAPHEME JOINER;Mn;0;NSM;;;;;N;;;;;
+0627;ARABIC LETTER ALEF;Lo;0;AL;;;;;N;;;;;
+0644;ARABIC LETTER LAM;Lo;0;AL;;;;;N;;;;;
+1160;HANGUL JUNGSEONG FILLER;Lo;0;L;;;;;N;;;;;
+1E00;LATIN CAPITAL LETTER A WITH RING BELOW;Lu;0;L;0041 0325;;;;N;;;;1E01;
+231A;WATCH;So;0;ON;;;;;N;;;;;
+4E00;<CJK Ideograph, First>;Lo;0;L;;;;;N;;;;;
+9FFF;<CJK Ideograph, Last>;Lo;0;L;;;;;N;;;;;
+1F1E6;REGIONAL INDICATOR SYMBOL LETTER A;So;0;L;;;;;N;;;;;
+1F3FB;EMOJI MODIFIER FITZPATRICK TYPE-1-2;Sk;0;ON;;;;;N;;;;;
+1F44D;THUMBS UP SIGN;So;0;ON;;;;;N;;;;;
+"
+        }
+        "EastAsianWidth.txt" => {
+            "\
+# EastAsianWidth-15.1.0.txt
+# @missing: 0000..10FFFF; N
+# @missing: 4E00..9FFF; W
+00A1;A           # Po         INVERTED EXCLAMATION MARK
+0300..036F;A     # Mn   [112] COMBINING GRAVE ACCENT..COMBINING LATIN SMALL LETTER X
+231A..231B;W     # So     [2] WATCH..HOURGLASS
+4E00..9FFF;W     # Lo [20992] CJK UNIFIED IDEOGRAPH-4E00..CJK UNIFIED IDEOGRAPH-9FFF
+1F44D;W          # So         THUMBS UP SIGN
+"
+        }
+        "DerivedCoreProperties.txt" => {
+            "\
+# DerivedCoreProperties-15.1.0.txt
+034F          ; Default_Ignorable_Code_Point # Mn       COMBINING GRAPHEME JOINER
+0300..036F    ; Grapheme_Extend # Mn [112] COMBINING GRAVE ACCENT..COMBINING LATIN SMALL LETTER X
+"
+        }
+        "PropList.txt" => "1F1E6..1F1FF ; Regional_Indicator # So [26] REGIONAL INDICATOR\n",
+        "HangulSyllableType.txt" => "1160..11A7    ; V # Lo [72] HANGUL JUNGSEONG FILLER\n",
+        "LineBreak.txt" => "00A1;OP           # Po         INVERTED EXCLAMATION MARK\n",
+        "ArabicShaping.txt" => "0627; ALEF; R; ALEF\n0644; LAM; D; LAM\n",
+        "extracted/DerivedJoiningType.txt" => "0300..036F    ; T # Mn [112] COMBINING\n",
+        "emoji/emoji-data.txt" => {
+            "\
+231A..231B    ; Emoji_Presentation   # E0.6   [2] (⌚..⌛)    watch..hourglass done
+1F3FB..1F3FF  ; Emoji_Presentation   # E1.0   [5] (🏻..🏿)    light skin tone..dark skin tone
+1F44D         ; Emoji_Presentation   # E0.6   [1] (👍)       thumbs up
+1F3FB..1F3FF  ; Emoji_Modifier       # E1.0   [5] (🏻..🏿)    light skin tone..dark skin tone
+1F44D         ; Emoji_Modifier_Base  # E0.6   [1] (👍)       thumbs up
+"
+        }
+        "emoji/emoji-variation-sequences.txt" => {
+            "\
+231A FE0E ; text style;  # (1.1) WATCH
+231A FE0F ; emoji style; # (1.1) WATCH
+"
+        }
+        "auxiliary/GraphemeBreakProperty.txt" => "",
+        _ => return Err(io::Error::new(io::ErrorKind::NotFound, name)),
+    };
+    Ok(text.to_string())
+}
+
+#[test]
+fn test_ucd_tables() {
+    let tables = Ucd::from_files(ucd_file).unwrap().tables();
+    let width = |cp: u32| tables.widths[cp as usize];
+    let width_cjk = |cp: u32| tables.widths_cjk[cp as usize];
+
+    assert_eq!(tables.version, (15, 1, 0));
+    assert_eq!(width(0x41), Width::Narrow);
+    assert_eq!(width(0xA), Width::Special(Special::LineFeed));
+    assert_eq!(width(0xA1), Width::Narrow);
+    assert_eq!(width_cjk(0xA1), Width::Wide);
+    assert_eq!(width(0x300), Width::Zero);
+    assert_eq!(width(0x1160), Width::Zero);
+    assert_eq!(width(0x627), Width::Special(Special::JoiningGroupAlef));
+    assert_eq!(width(0x4E00), Width::Wide);
+    // Unassigned, in a block that defaults to wide
+    assert_eq!(width(0x9FFE), Width::Wide);
+    assert_eq!(width(0x1F44D), Width::Special(Special::EmojiPresentation));
+    assert_eq!(width(0x1F1E6), Width::Special(Special::RegionalIndicator));
+    assert_eq!(width(0x1F3FB), Width::Special(Special::EmojiModifier));
+
+    assert_eq!(tables.joining_group_lam, BTreeSet::from([0x644]));
+    assert_eq!(tables.ligature_transparent, BTreeSet::from([0x34F, 0x200D]));
+    assert!(tables.solidus_transparent.contains(&0x300));
+    assert!(!tables.solidus_transparent.contains(&0x1E00));
+    assert!(tables.non_transparent_zero_widths.contains(&0x1160));
+    assert!(!tables.non_transparent_zero_widths.contains(&0x300));
+    assert_eq!(tables.emoji_presentation_seq, BTreeSet::from([0x231A]));
+    assert_eq!(tables.text_presentation_seq, BTreeSet::from([0x231A]));
+    assert_eq!(tables.emoji_modifier_base, BTreeSet::from([0x1F44D]));
+}
+
+#[test]
+fn test_ucd_missing_file() {
+    let err = Ucd::from_files(|name| match name {
+        "UnicodeData.txt" => ucd_file(name),
+        _ => Err(io::Error::new(io::ErrorKind::NotFound, name)),
+    })
+    .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+/// Regenerating the tables from the UCD they were built from changes
+/// nothing. Run with `UCD_DIR` set to an unzipped Unicode 15.1 `UCD.zip`:
+/// `UCD_DIR=path/to/UCD cargo test -p unicode_width_gen -- --ignored`.
+#[test]
+#[ignore = "needs UCD_DIR, an unzipped UCD.zip"]
+fn test_ucd_reproduces_tables() -> io::Result<()> {
+    let dir = env::var_os("UCD_DIR").expect("UCD_DIR is not set");
+    let src = fs::read_to_string(TABLES_RS)?;
+    let spliced = splice(&src, &Ucd::load(Path::new(&dir))?.tables())?;
+    assert_unchanged(&src, &spliced);
+    Ok(())
+}
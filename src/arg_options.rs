@@ -95,23 +95,102 @@
 //! }
 //! ```
 
-use result_error::{Fail, Matches, Opt, Optval};
-use std::ffi::OsStr;
-use unicode_width::UnicodeWidthStr;
+use result_error::{Fail, Matches, Opt, Optval, Source};
+use std::ffi::{OsStr, OsString};
+use unicode_width::{pad_to_width_ansi, pad_to_width_ansi_cjk, Align, UnicodeWidthStr};
 
 use crate::{
-    global_fn::{self, each_split_within},
-    optgroup::{HasArg, Name, Occur, OptGroup, ParsingStyle},
+    config::Config,
+    global_fn::{self, each_split_within_by},
+    optgroup::{Constraint, HasArg, Name, Occur, OptGroup, ParsingStyle},
     result_error::{self, Result},
 };
 
-use global_fn::{find_opt, is_arg, validate_names};
+use global_fn::{
+    check_constraint, expand_response_files, find_opt, find_opt_prefix, is_arg, resolve_vals,
+    suggest_opt, validate_names,
+};
+
+/// Convert an option, which must be valid UTF-8. The invalid bytes of
+/// `--name=VALUE` or `-nVALUE` are blamed on the value when the option is
+/// known.
+fn into_utf8(arg: OsString, opts: &[Opt]) -> ::std::result::Result<String, Fail> {
+    arg.into_string().map_err(|arg| {
+        let lossy = arg.to_string_lossy();
+        let takes_arg =
+            |name: &Name| find_opt(opts, name).is_some_and(|id| opts[id].hasarg != HasArg::No);
+        let name = if let Some(long) = lossy.strip_prefix("--") {
+            long.split_once('=')
+                .map(|(name, _)| Name::from_str(name))
+                .filter(takes_arg)
+        } else {
+            lossy
+                .chars()
+                .skip(1)
+                .map(Name::Short)
+                .take_while(|name| find_opt(opts, name).is_some())
+                .find(takes_arg)
+        };
+        match name {
+            Some(name) => Fail::InvalidUtf8Argument(as_typed(&name)),
+            None => Fail::UnrecognizedOption(lossy.into_owned(), None),
+        }
+    })
+}
+
+/// Convert the argument of the option `nm`, which must be valid UTF-8.
+fn arg_into_utf8(arg: OsString, nm: &Name) -> ::std::result::Result<String, Fail> {
+    arg.into_string()
+        .map_err(|_| Fail::InvalidUtf8Argument(as_typed(nm)))
+}
+
+/// `nm` as it is written on the command line: `-n` or `--lines`.
+fn as_typed(nm: &Name) -> String {
+    match *nm {
+        Name::Short(ch) => format!("-{}", ch),
+        Name::Long(ref long) => format!("--{}", long),
+    }
+}
+
+/// Terminal width the usage help is laid out for when none is detected.
+pub const DEFAULT_TERM_WIDTH: usize = 80;
+
+/// Returns the width of the option column and of the description column
+/// of the usage help for a terminal `term_width` columns wide.
+///
+/// An 80 column terminal gets the classic getopts layout: options in the
+/// first 24 columns, descriptions wrapped at 54.
+pub fn usage_columns(term_width: usize) -> (usize, usize) {
+    let line_width = term_width.saturating_sub(2);
+    let opt_width = (line_width.saturating_mul(24) / 78).clamp(16, 40);
+    let desc_width = line_width.saturating_sub(opt_width).max(20);
+    (opt_width, desc_width)
+}
+
+/// A command such as `rtail stats` that has options of its own.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Subcommand {
+    pub name: String,
+    pub desc: String,
+    pub options: Options,
+}
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Options {
     pub grps: Vec<OptGroup>,
+    pub constraints: Vec<Constraint>,
+    /// `(option name, environment variable)` bindings
+    pub env_vars: Vec<(String, String)>,
+    /// `(option name, value)` documented defaults
+    pub defaults: Vec<(String, String)>,
+    pub subcommands: Vec<Subcommand>,
     parsing_style: ParsingStyle,
     long_only: bool,
+    long_prefixes: bool,
+    response_files: bool,
+    section: Option<String>,
+    term_width: usize,
+    east_asian_width: bool,
 }
 
 impl Default for Options {
@@ -125,8 +204,17 @@ impl Options {
     pub fn new() -> Options {
         Options {
             grps: Vec::new(),
+            constraints: Vec::new(),
+            env_vars: Vec::new(),
+            defaults: Vec::new(),
+            subcommands: Vec::new(),
             parsing_style: ParsingStyle::FloatingFrees,
             long_only: false,
+            long_prefixes: false,
+            response_files: false,
+            section: None,
+            term_width: DEFAULT_TERM_WIDTH,
+            east_asian_width: false,
         }
     }
 
@@ -144,7 +232,9 @@ impl Options {
             hint: hint.to_string(),
             desc: desc.to_string(),
             hasarg: HasArg::Yes,
-            occur: Occur::Req,
+            occur: Occur::Optional,
+            section: self.section.clone(),
+            negatable: false,
         });
         self
     }
@@ -158,10 +248,194 @@ impl Options {
             desc: desc.to_string(),
             hasarg: HasArg::No,
             occur: Occur::Optional,
+            section: self.section.clone(),
+            negatable: false,
+        });
+        self
+    }
+
+    /// Create an option that may be given with or without an argument,
+    /// e.g. `--help` and `--help=json`. The argument can only be attached
+    /// to the long name with `=`; the short name is a plain flag, so `-h FILE`
+    /// does not take `FILE` as its argument.
+    pub fn optflagopt(
+        &mut self,
+        short_name: &str,
+        long_name: &str,
+        desc: &str,
+        hint: &str,
+    ) -> &mut Options {
+        validate_names(short_name, long_name);
+        assert!(
+            !long_name.is_empty(),
+            "an optional argument can only be given to a long name"
+        );
+        self.grps.push(OptGroup {
+            short_name: short_name.to_string(),
+            long_name: long_name.to_string(),
+            hint: hint.to_string(),
+            desc: desc.to_string(),
+            hasarg: HasArg::Maybe,
+            occur: Occur::Optional,
+            section: self.section.clone(),
+            negatable: false,
         });
         self
     }
 
+    /// Create a flag that can be turned off again with `--no-<long_name>`.
+    /// The last of `--<long_name>` and `--no-<long_name>` wins.
+    pub fn optflag_negatable(
+        &mut self,
+        short_name: &str,
+        long_name: &str,
+        desc: &str,
+    ) -> &mut Options {
+        assert!(!long_name.is_empty(), "a negatable flag needs a long name");
+        validate_names(short_name, long_name);
+        self.grps.push(OptGroup {
+            short_name: short_name.to_string(),
+            long_name: long_name.to_string(),
+            hint: "".to_string(),
+            desc: desc.to_string(),
+            hasarg: HasArg::No,
+            occur: Occur::LastWins,
+            section: self.section.clone(),
+            negatable: true,
+        });
+        self
+    }
+
+    /// Let option `name` be given more than once, the last occurrence
+    /// overriding the earlier ones.
+    pub fn last_wins(&mut self, name: &str) -> &mut Options {
+        match self
+            .grps
+            .iter_mut()
+            .find(|grp| grp.short_name == name || grp.long_name == name)
+        {
+            Some(grp) => grp.occur = Occur::LastWins,
+            None => panic!("No option '{}' defined", name),
+        }
+        self
+    }
+
+    /// Start a new section of the usage help. Options added after this call
+    /// are listed under `title` instead of the default `Options:` heading.
+    pub fn section(&mut self, title: &str) -> &mut Options {
+        self.section = Some(title.to_string());
+        self
+    }
+
+    /// Lay the usage help out for a terminal `width` columns wide instead of
+    /// `DEFAULT_TERM_WIDTH`.
+    pub fn term_width(&mut self, width: usize) -> &mut Options {
+        self.term_width = width;
+        for sub in self.subcommands.iter_mut() {
+            sub.options.term_width(width);
+        }
+        self
+    }
+
+    /// Measure the usage help with East Asian widths, where ambiguous
+    /// characters take two columns.
+    pub fn east_asian_width(&mut self, east_asian: bool) -> &mut Options {
+        self.east_asian_width = east_asian;
+        for sub in self.subcommands.iter_mut() {
+            sub.options.east_asian_width(east_asian);
+        }
+        self
+    }
+
+    /// Register the subcommand `name` and return its options for setting up.
+    ///
+    /// When the first free argument is `name`, the arguments after it are
+    /// parsed with the subcommand's options and end up in
+    /// `Matches::subcommand`.
+    #[allow(unused)]
+    pub fn subcommand(&mut self, name: &str, desc: &str) -> &mut Options {
+        assert!(
+            !name.is_empty() && !is_arg(name),
+            "invalid subcommand name '{}'",
+            name
+        );
+        assert!(
+            self.subcommands.iter().all(|sub| sub.name != name),
+            "subcommand '{}' defined more than once",
+            name
+        );
+        let mut options = Options::new();
+        options.term_width = self.term_width;
+        options.east_asian_width = self.east_asian_width;
+        options.long_prefixes = self.long_prefixes;
+        self.subcommands.push(Subcommand {
+            name: name.to_string(),
+            desc: desc.to_string(),
+            options,
+        });
+        &mut self.subcommands.last_mut().unwrap().options
+    }
+
+    /// Accept any unambiguous prefix of a long option, e.g. `--foll` for
+    /// `--follow`.
+    pub fn long_prefixes(&mut self, enable: bool) -> &mut Options {
+        self.long_prefixes = enable;
+        self
+    }
+
+    /// Replace `@file` arguments with the arguments read from `file`.
+    pub fn response_files(&mut self, enable: bool) -> &mut Options {
+        self.response_files = enable;
+        self
+    }
+
+    /// Bind option `name` to the environment variable `var`, see
+    /// `parse_with_defaults()`.
+    pub fn env_var(&mut self, name: &str, var: &str) -> &mut Options {
+        self.env_vars.push((name.to_string(), var.to_string()));
+        self
+    }
+
+    /// Record the value option `name` has when it is not given. This is only
+    /// documentation for `json_schema()` and the like, `parse()` does not
+    /// fill it in.
+    pub fn default_value(&mut self, name: &str, value: &str) -> &mut Options {
+        self.defaults.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Returns the value recorded with `default_value()` for option `name`.
+    pub fn get_default(&self, name: &str) -> Option<&str> {
+        self.defaults
+            .iter()
+            .find(|(nm, _)| nm == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Reject the arguments if both `a` and `b` are given.
+    pub fn conflicts_with(&mut self, a: &str, b: &str) -> &mut Options {
+        self.constraints
+            .push(Constraint::ConflictsWith(a.to_string(), b.to_string()));
+        self
+    }
+
+    /// Reject the arguments if `name` is given without `required`.
+    #[allow(unused)]
+    pub fn requires(&mut self, name: &str, required: &str) -> &mut Options {
+        self.constraints
+            .push(Constraint::Requires(name.to_string(), required.to_string()));
+        self
+    }
+
+    /// Reject the arguments unless exactly one of `names` is given.
+    #[allow(unused)]
+    pub fn one_of(&mut self, names: &[&str]) -> &mut Options {
+        self.constraints.push(Constraint::OneOf(
+            names.iter().map(|nm| nm.to_string()).collect(),
+        ));
+        self
+    }
+
     // pub fn opt_strs(&self, name: &str) -> Vec<String> {
     //     self.opt_vals(name)
     //         .into_iter()
@@ -179,48 +453,86 @@ impl Options {
     //     }
     // }
 
+    #[allow(unused)]
     pub fn parse<C>(&self, args: C) -> Result
     where
         C: IntoIterator,
         C::Item: AsRef<OsStr>,
     {
+        self.parse_with_defaults(args, |_| None, None)
+    }
+
+    /// Parse `args` like `parse()`, then give options that were not on the
+    /// command line their value from the environment variable bound with
+    /// `env_var()` (read through `env`) or, failing that, from `config`.
+    ///
+    /// A default is skipped if it would conflict with an option that is
+    /// already set, so `-c` on the command line wins over a configured line
+    /// count.
+    ///
+    /// Subcommands see the same environment, but not `config`.
+    pub fn parse_with_defaults<C, E>(&self, args: C, env: E, config: Option<&Config>) -> Result
+    where
+        C: IntoIterator,
+        C::Item: AsRef<OsStr>,
+        E: Fn(&str) -> Option<String>,
+    {
+        let mut args = args
+            .into_iter()
+            .map(|i| i.as_ref().to_os_string())
+            .collect();
+        if self.response_files {
+            args = expand_response_files(args)?;
+        }
+        self.parse_args(args, &env, config)
+    }
+
+    /// Options and their arguments must be valid UTF-8, free arguments are
+    /// kept as they were given in `Matches::free_os`.
+    fn parse_args(
+        &self,
+        args: Vec<OsString>,
+        env: &dyn Fn(&str) -> Option<String>,
+        config: Option<&Config>,
+    ) -> Result {
         let opts: Vec<Opt> = self.grps.iter().map(|x| x.long_to_short()).collect();
+        let negatable: Vec<bool> = self.grps.iter().map(|grp| grp.negatable).collect();
 
         let mut vals = (0..opts.len())
             .map(|_| Vec::new())
             .collect::<Vec<Vec<(usize, Optval)>>>();
-        let mut free: Vec<String> = Vec::new();
+        let mut free_os: Vec<OsString> = Vec::new();
         let mut args_end = None;
+        let mut subcommand = None;
 
-        let args = args
-            .into_iter()
-            .map(|i| {
-                i.as_ref()
-                    .to_str()
-                    .ok_or_else(|| Fail::UnrecognizedOption(format!("{:?}", i.as_ref())))
-                    .map(|s| s.to_owned())
-            })
-            .collect::<::std::result::Result<Vec<_>, _>>()?;
         let mut args = args.into_iter().peekable();
         let mut arg_pos = 0;
-        while let Some(cur) = args.next() {
+        while let Some(cur_os) = args.next() {
+            let cur = cur_os.to_string_lossy();
             if !is_arg(&cur) {
-                free.push(cur);
+                if free_os.is_empty() {
+                    if let Some(sub) = self.subcommands.iter().find(|sub| sub.name == cur) {
+                        let matches = sub.options.parse_args(args.collect(), env, None)?;
+                        subcommand = Some((sub.name.clone(), Box::new(matches)));
+                        break;
+                    }
+                }
+                free_os.push(cur_os);
                 match self.parsing_style {
                     ParsingStyle::FloatingFrees => {}
                     ParsingStyle::StopAtFirstFree => {
-                        free.extend(args);
+                        free_os.extend(args);
                         break;
                     }
                 }
             } else if cur == "--" {
-                args_end = Some(free.len());
-                free.extend(args);
+                args_end = Some(free_os.len());
+                free_os.extend(args);
                 break;
             } else {
+                let cur = into_utf8(cur_os, &opts)?;
                 let mut name = None;
                 let mut i_arg = None;
-                let mut was_long = true;
                 if cur.as_bytes()[1] == b'-' || self.long_only {
                     let tail = if cur.as_bytes()[1] == b'-' {
                         &cur[2..]
@@ -234,23 +546,28 @@ impl Options {
                         i_arg = Some(rest.to_string());
                     }
                 } else {
-                    was_long = false;
                     for (j, ch) in cur.char_indices().skip(1) {
                         let opt = Name::Short(ch);
 
                         let opt_id = match find_opt(&opts, &opt) {
                             Some(id) => id,
-                            None => return Err(Fail::UnrecognizedOption(opt.to_string())),
+                            None => {
+                                return Err(Fail::UnrecognizedOption(
+                                    format!("-{}", ch),
+                                    suggest_opt(&opts, &negatable, &cur[1..]),
+                                ))
+                            }
                         };
 
                         // In a series of potential options (eg. -aheJ), if we
                         // see one which takes an argument, we assume all
                         // subsequent characters make up the argument. This
                         // allows options such as -L/usr/local/lib/foo to be
-                        // interpreted correctly
+                        // interpreted correctly. The short name of a "Maybe"
+                        // option is a plain flag.
                         let arg_follows = match opts[opt_id].hasarg {
-                            HasArg::Yes | HasArg::Maybe => true,
-                            HasArg::No => false,
+                            HasArg::Yes => true,
+                            HasArg::No | HasArg::Maybe => false,
                         };
 
                         if arg_follows {
@@ -266,16 +583,64 @@ impl Options {
                     }
                 }
                 if let Some(nm) = name {
+                    let dashes = if cur.as_bytes()[1] == b'-' { "--" } else { "-" };
+                    let prefix = match nm {
+                        Name::Long(ref prefix) if self.long_prefixes => Some(prefix),
+                        _ => None,
+                    };
+                    let negation = match nm {
+                        Name::Long(ref long) => long
+                            .strip_prefix("no-")
+                            .and_then(|base| find_opt(&opts, &Name::Long(base.to_string())))
+                            .filter(|id| self.grps[*id].negatable),
+                        _ => None,
+                    };
+                    let mut negated = false;
                     let opt_id = match find_opt(&opts, &nm) {
+                        Some(id) => Some(id),
+                        None if negation.is_some() => {
+                            negated = true;
+                            negation
+                        }
+                        None => {
+                            match prefix.map(|prefix| find_opt_prefix(&opts, &negatable, prefix)) {
+                                Some(found) if found.len() == 1 => {
+                                    negated = found[0].2;
+                                    Some(found[0].0)
+                                }
+                                Some(found) if found.len() > 1 => {
+                                    return Err(Fail::AmbiguousOption(
+                                        cur[..cur.find('=').unwrap_or(cur.len())].to_string(),
+                                        found
+                                            .into_iter()
+                                            .map(|(_, nm, _)| format!("{}{}", dashes, nm))
+                                            .collect(),
+                                    ));
+                                }
+                                _ => None,
+                            }
+                        }
+                    };
+                    let opt_id = match opt_id {
                         Some(id) => id,
-                        None => return Err(Fail::UnrecognizedOption(nm.to_string())),
+                        None => {
+                            return Err(Fail::UnrecognizedOption(
+                                format!("{}{}", dashes, nm),
+                                suggest_opt(&opts, &negatable, &nm.to_string()),
+                            ));
+                        }
                     };
                     match opts[opt_id].hasarg {
                         HasArg::No => {
                             if i_arg.is_some() {
                                 return Err(Fail::UnexpectedArgument(nm.to_string()));
                             }
-                            vals[opt_id].push((arg_pos, Optval::Given));
+                            let val = if negated {
+                                Optval::Negated
+                            } else {
+                                Optval::Given
+                            };
+                            vals[opt_id].push((arg_pos, val));
                         }
                         HasArg::Maybe => {
                             // Note that here we do not handle `--arg value`.
@@ -284,19 +649,18 @@ impl Options {
                             // then users could only write a "Maybe" long
                             // option at the end of the arguments when
                             // FloatingFrees is in use.
+                            // Short names never get here, see above.
                             if let Some(i_arg) = i_arg.take() {
                                 vals[opt_id].push((arg_pos, Optval::Val(i_arg)));
-                            } else if was_long || args.peek().map_or(true, |n| is_arg(n)) {
-                                vals[opt_id].push((arg_pos, Optval::Given));
                             } else {
-                                vals[opt_id].push((arg_pos, Optval::Val(args.next().unwrap())));
+                                vals[opt_id].push((arg_pos, Optval::Given));
                             }
                         }
                         HasArg::Yes => {
                             if let Some(i_arg) = i_arg.take() {
                                 vals[opt_id].push((arg_pos, Optval::Val(i_arg)));
                             } else if let Some(n) = args.next() {
-                                vals[opt_id].push((arg_pos, Optval::Val(n)));
+                                vals[opt_id].push((arg_pos, Optval::Val(arg_into_utf8(n, &nm)?)));
                             } else {
                                 return Err(Fail::ArgumentMissing(nm.to_string()));
                             }
@@ -308,32 +672,140 @@ impl Options {
         }
         debug_assert_eq!(vals.len(), opts.len());
         for (vals, opt) in vals.iter().zip(opts.iter()) {
-            if opt.occur == Occur::Req && vals.is_empty() {
-                return Err(Fail::OptionMissing(opt.name.to_string()));
-            }
-            if opt.occur != Occur::Multi && vals.len() > 1 {
+            let repeatable = opt.occur == Occur::Multi || opt.occur == Occur::LastWins;
+            if !repeatable && vals.len() > 1 {
                 return Err(Fail::OptionDuplicated(opt.name.to_string()));
             }
         }
 
+        let mut sources: Vec<Option<Source>> = vals
+            .iter()
+            .map(|vals| (!vals.is_empty()).then_some(Source::CommandLine))
+            .collect();
+        for (nm, var) in self.env_vars.iter() {
+            if let Some(value) = env(var) {
+                let opt_id = match find_opt(&opts, &Name::from_str(nm)) {
+                    Some(id) => id,
+                    None => panic!("No option '{}' defined", nm),
+                };
+                let source = Source::Env(var.clone());
+                self.apply_default(&opts, &mut vals, &mut sources, opt_id, &value, source)?;
+            }
+        }
+        if let Some(config) = config {
+            for (key, value) in config.values.iter() {
+                let opt_id = match find_opt(&opts, &Name::from_str(key)) {
+                    Some(id) => id,
+                    None => {
+                        let path = config.path.display().to_string();
+                        return Err(Fail::UnknownSetting(path, key.to_string()));
+                    }
+                };
+                let source = Source::Config(config.path.clone());
+                self.apply_default(&opts, &mut vals, &mut sources, opt_id, value, source)?;
+            }
+        }
+
+        for (vals, opt) in vals.iter().zip(opts.iter()) {
+            if opt.occur == Occur::Req && resolve_vals(opt, vals).is_empty() {
+                return Err(Fail::OptionMissing(opt.name.to_string()));
+            }
+        }
+        for constraint in self.constraints.iter() {
+            check_constraint(constraint, &opts, &vals)?;
+        }
+
         // Note that if "--" is last argument on command line, then index stored
         // in option does not exist in `free` and must be replaced with `None`
-        args_end = args_end.filter(|pos| pos != &free.len());
+        args_end = args_end.filter(|pos| pos != &free_os.len());
 
+        let free = free_os
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
         Ok(Matches {
             opts,
             vals,
             free,
+            free_os,
             args_end,
+            sources,
+            subcommand,
         })
     }
 
+    /// Set option `opt_id` to the default `value` read from `source`, unless
+    /// it is already set or conflicts with an option that is.
+    fn apply_default(
+        &self,
+        opts: &[Opt],
+        vals: &mut [Vec<(usize, Optval)>],
+        sources: &mut [Option<Source>],
+        opt_id: usize,
+        value: &str,
+        source: Source,
+    ) -> ::std::result::Result<(), Fail> {
+        if !vals[opt_id].is_empty() {
+            return Ok(());
+        }
+        let is_set = |nm: &str| {
+            find_opt(opts, &Name::from_str(nm))
+                .is_some_and(|id| !resolve_vals(&opts[id], &vals[id]).is_empty())
+        };
+        let is_this = |nm: &str| find_opt(opts, &Name::from_str(nm)) == Some(opt_id);
+        let conflicts = self.constraints.iter().any(|constraint| match *constraint {
+            Constraint::ConflictsWith(ref a, ref b) => {
+                (is_this(a) && is_set(b)) || (is_this(b) && is_set(a))
+            }
+            _ => false,
+        });
+        if conflicts {
+            return Ok(());
+        }
+
+        let pos = vals
+            .iter()
+            .flatten()
+            .map(|(pos, _)| pos + 1)
+            .max()
+            .unwrap_or(0);
+        match opts[opt_id].hasarg {
+            HasArg::No => match value {
+                "true" | "1" | "yes" | "on" => vals[opt_id].push((pos, Optval::Given)),
+                "false" | "0" | "no" | "off" | "" => return Ok(()),
+                _ => return Err(Fail::UnexpectedArgument(opts[opt_id].name.to_string())),
+            },
+            HasArg::Yes | HasArg::Maybe => {
+                vals[opt_id].push((pos, Optval::Val(value.to_string())));
+            }
+        }
+        sources[opt_id] = Some(source);
+        Ok(())
+    }
+
     #[allow(unused)]
     fn clone(&self) -> Self {
         Options {
             grps: self.grps.clone(),
+            constraints: self.constraints.clone(),
+            env_vars: self.env_vars.clone(),
+            defaults: self.defaults.clone(),
+            subcommands: self
+                .subcommands
+                .iter()
+                .map(|sub| Subcommand {
+                    name: sub.name.clone(),
+                    desc: sub.desc.clone(),
+                    options: sub.options.clone(),
+                })
+                .collect(),
             parsing_style: self.parsing_style.clone(),
             long_only: self.long_only,
+            long_prefixes: self.long_prefixes,
+            response_files: self.response_files,
+            section: self.section.clone(),
+            term_width: self.term_width,
+            east_asian_width: self.east_asian_width,
         }
     }
     /// Create a long option that is required and takes an argument.
@@ -376,21 +848,48 @@ impl Options {
             desc: desc.to_string(),
             hasarg: HasArg::Yes,
             occur: Occur::Req,
+            section: self.section.clone(),
+            negatable: false,
         });
         self
     }
-    /// Derive a formatted message from a set of options.
+    /// Derive a formatted message from a set of options. Options added after
+    /// a call to `section()` are listed under that section's heading.
     pub fn usage(&self, brief: &str) -> String {
-        self.usage_with_format(|opts| {
-            format!(
-                "{}\n\nOptions:\n{}\n",
-                brief,
-                opts.collect::<Vec<String>>().join("\n")
-            )
-        })
+        format!("{}\n\n{}\n", brief, self.usage_sections())
+    }
+
+    /// Derive the option list of the usage message, one block per section.
+    /// Subcommands are listed last, under `Commands:`.
+    pub fn usage_sections(&self) -> String {
+        let mut sections: Vec<(&str, Vec<String>)> = Vec::new();
+        for (grp, row) in self.grps.iter().zip(self.usage_items()) {
+            let title = grp.section.as_deref().unwrap_or("Options");
+            match sections.last_mut() {
+                Some((last, rows)) if *last == title => rows.push(row),
+                _ => sections.push((title, vec![row])),
+            }
+        }
+        if sections.is_empty() {
+            sections.push(("Options", Vec::new()));
+        }
+        if !self.subcommands.is_empty() {
+            let rows = self
+                .subcommands
+                .iter()
+                .map(|sub| self.usage_row(format!("    {} ", sub.name), &sub.desc))
+                .collect();
+            sections.push(("Commands", rows));
+        }
+        sections
+            .iter()
+            .map(|(title, rows)| format!("{}:\n{}", title, rows.join("\n")))
+            .collect::<Vec<String>>()
+            .join("\n\n")
     }
     /// Derive a custom formatted message from a set of options. The formatted options provided to
     /// a closure as an iterator.
+    #[allow(unused)]
     pub fn usage_with_format<F>(&self, mut formatter: F) -> String
     where
         F: FnMut(&mut dyn Iterator<Item = String>) -> String,
@@ -398,10 +897,40 @@ impl Options {
         formatter(&mut self.usage_items())
     }
 
+    fn usage_width(&self) -> fn(&str) -> usize {
+        if self.east_asian_width {
+            UnicodeWidthStr::width_ansi_cjk
+        } else {
+            UnicodeWidthStr::width_ansi
+        }
+    }
+
+    /// Pad `row` to the option column and append `desc` wrapped to the
+    /// description column.
+    fn usage_row(&self, mut row: String, desc: &str) -> String {
+        let (opt_width, desc_width) = usage_columns(self.term_width);
+        let width = self.usage_width();
+        let desc_sep = format!("\n{}", " ".repeat(opt_width));
+
+        if width(&row) < opt_width {
+            let pad = if self.east_asian_width {
+                pad_to_width_ansi_cjk
+            } else {
+                pad_to_width_ansi
+            };
+            row = pad(&row, opt_width, Align::Left).to_string();
+        } else {
+            row.push_str(&desc_sep)
+        }
+
+        let desc_rows = each_split_within_by(desc, desc_width, width);
+        row.push_str(&desc_rows.join(&desc_sep));
+
+        row
+    }
+
     /// Derive usage items from a set of options.
     fn usage_items<'a>(&'a self) -> Box<dyn Iterator<Item = String> + 'a> {
-        let desc_sep = format!("\n{}", " ".repeat(24));
-
         let any_short = self.grps.iter().any(|optref| !optref.short_name.is_empty());
 
         let rows = self.grps.iter().map(move |optref| {
@@ -411,6 +940,7 @@ impl Options {
                 hint,
                 desc,
                 hasarg,
+                negatable,
                 ..
             } = (*optref).clone();
 
@@ -443,6 +973,9 @@ impl Options {
                 0 => {}
                 _ => {
                     row.push_str(if self.long_only { "-" } else { "--" });
+                    if negatable {
+                        row.push_str("[no-]");
+                    }
                     row.push_str(&long_name);
                     row.push(' ');
                 }
@@ -453,25 +986,15 @@ impl Options {
                 HasArg::No => {}
                 HasArg::Yes => row.push_str(&hint),
                 HasArg::Maybe => {
-                    row.push('[');
+                    // Attached to the long name, e.g. `--help[=FORMAT]`.
+                    row.pop();
+                    row.push_str("[=");
                     row.push_str(&hint);
                     row.push(']');
                 }
             }
 
-            let rowlen = row.width();
-            if rowlen < 24 {
-                for _ in 0..24 - rowlen {
-                    row.push(' ');
-                }
-            } else {
-                row.push_str(&desc_sep)
-            }
-
-            let desc_rows = each_split_within(&desc, 54);
-            row.push_str(&desc_rows.join(&desc_sep));
-
-            row
+            self.usage_row(row, &desc)
         });
 
         Box::new(rows)
@@ -0,0 +1,205 @@
+//! Shell completion scripts generated from the `OptGroup`s of an `Options`.
+//!
+//! The argument hint decides how an option's value is completed: `FILE` and
+//! `PATH` complete file names, `DIR` completes directories and any other hint
+//! (e.g. `NUMS`) offers no completion at all.
+
+use crate::{
+    arg_options::Options,
+    optgroup::{HasArg, OptGroup},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    None,
+    File,
+    Dir,
+}
+
+impl Shell {
+    pub fn from_str(name: &str) -> Option<Shell> {
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+}
+
+fn value_kind(grp: &OptGroup) -> ValueKind {
+    match grp.hint.as_str() {
+        "FILE" | "PATH" => ValueKind::File,
+        "DIR" => ValueKind::Dir,
+        _ => ValueKind::None,
+    }
+}
+
+fn flags(grp: &OptGroup) -> Vec<String> {
+    let mut flags = Vec::new();
+    if !grp.short_name.is_empty() {
+        flags.push(format!("-{}", grp.short_name));
+    }
+    if !grp.long_name.is_empty() {
+        flags.push(format!("--{}", grp.long_name));
+    }
+    if grp.negatable {
+        flags.push(format!("--no-{}", grp.long_name));
+    }
+    flags
+}
+
+/// Generate a completion script for `shell` that completes the options of
+/// `options` for the command `program`. Free arguments complete as files.
+pub fn generate(shell: Shell, program: &str, options: &Options) -> String {
+    match shell {
+        Shell::Bash => bash(program, options),
+        Shell::Zsh => zsh(program, options),
+        Shell::Fish => fish(program, options),
+    }
+}
+
+fn bash(program: &str, options: &Options) -> String {
+    let func = format!("_{}", program.replace(|c: char| !c.is_alphanumeric(), "_"));
+    let all_flags = options
+        .grps
+        .iter()
+        .flat_map(flags)
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    let mut cases = String::new();
+    for grp in options.grps.iter().filter(|grp| grp.hasarg == HasArg::Yes) {
+        let action = match value_kind(grp) {
+            ValueKind::None => "",
+            ValueKind::File => "COMPREPLY=( $(compgen -f -- \"$cur\") )\n            ",
+            ValueKind::Dir => "COMPREPLY=( $(compgen -d -- \"$cur\") )\n            ",
+        };
+        cases.push_str(&format!(
+            "        {})\n            {}return 0\n            ;;\n",
+            flags(grp).join("|"),
+            action
+        ));
+    }
+
+    let mut script = format!(
+        "{func}() {{\n    local cur prev\n    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    \
+         prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n"
+    );
+    if !cases.is_empty() {
+        script.push_str(&format!("    case \"$prev\" in\n{}    esac\n", cases));
+    }
+    script.push_str(&format!(
+        "    if [[ \"$cur\" == -* ]]; then\n        \
+         COMPREPLY=( $(compgen -W \"{all_flags}\" -- \"$cur\") )\n        \
+         return 0\n    fi\n    \
+         COMPREPLY=( $(compgen -f -- \"$cur\") )\n}}\n\
+         complete -F {func} {program}\n"
+    ));
+    script
+}
+
+fn zsh_escape(s: &str) -> String {
+    s.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn zsh(program: &str, options: &Options) -> String {
+    let mut specs = Vec::new();
+    for grp in options.grps.iter() {
+        let names = flags(grp);
+        let desc = zsh_escape(grp.desc.lines().next().unwrap_or(""));
+        let action = match value_kind(grp) {
+            ValueKind::None => " ",
+            ValueKind::File => "_files",
+            ValueKind::Dir => "_files -/",
+        };
+        let arg = match grp.hasarg {
+            HasArg::No => String::new(),
+            HasArg::Yes => format!(":{}:{}", zsh_escape(&grp.hint), action),
+            HasArg::Maybe => {
+                // The short name is a plain flag; only `--name=VALUE` takes
+                // the optional argument.
+                let exclusive = names.join(" ");
+                if !grp.short_name.is_empty() {
+                    specs.push(format!("'({})-{}[{}]'", exclusive, grp.short_name, desc));
+                }
+                specs.push(format!(
+                    "'({})--{}=-[{}]::{}:{}'",
+                    exclusive,
+                    grp.long_name,
+                    desc,
+                    zsh_escape(&grp.hint),
+                    action
+                ));
+                continue;
+            }
+        };
+        let spec = if names.len() > 1 {
+            format!(
+                "'({})'{{{}}}'[{}]{}'",
+                names.join(" "),
+                names.join(","),
+                desc,
+                arg
+            )
+        } else {
+            format!("'{}[{}]{}'", names[0], desc, arg)
+        };
+        specs.push(spec);
+    }
+    specs.push("'*:FILE:_files'".to_string());
+
+    format!(
+        "#compdef {program}\n\n_{program}() {{\n    _arguments -s \\\n        {}\n}}\n\n_{program} \"$@\"\n",
+        specs.join(" \\\n        ")
+    )
+}
+
+fn fish_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish(program: &str, options: &Options) -> String {
+    let mut script = String::new();
+    for grp in options.grps.iter() {
+        let mut line = format!("complete -c {}", program);
+        if !grp.short_name.is_empty() {
+            line.push_str(&format!(" -s {}", grp.short_name));
+        }
+        if !grp.long_name.is_empty() {
+            line.push_str(&format!(" -l {}", grp.long_name));
+        }
+        match (&grp.hasarg, value_kind(grp)) {
+            (HasArg::No, _) => {}
+            (HasArg::Yes, ValueKind::None) => line.push_str(" -x"),
+            (HasArg::Yes, ValueKind::File) => line.push_str(" -r -F"),
+            (HasArg::Yes, ValueKind::Dir) => {
+                line.push_str(" -x -a '(__fish_complete_directories)'")
+            }
+            // An optional argument doesn't stop files completing after it
+            (HasArg::Maybe, ValueKind::None) => {}
+            (HasArg::Maybe, ValueKind::File) => line.push_str(" -F"),
+            (HasArg::Maybe, ValueKind::Dir) => line.push_str(" -a '(__fish_complete_directories)'"),
+        }
+        let desc = fish_escape(grp.desc.lines().next().unwrap_or(""));
+        line.push_str(&format!(" -d '{}'\n", desc));
+        if grp.negatable {
+            line.push_str(&format!(
+                "complete -c {} -l no-{} -d '{}'\n",
+                program, grp.long_name, desc
+            ));
+        }
+        script.push_str(&line);
+    }
+    script
+}
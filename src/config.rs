@@ -0,0 +1,132 @@
+//! Option defaults read from `~/.config/rtail/config.toml`.
+//!
+//! Only flat `key = value` lines are understood, where the key is the long
+//! (or short) name of an option and the value is a string, a number or a
+//! boolean:
+//!
+//! ```toml
+//! # always show 20 lines and poll every 2 seconds
+//! lines = 20
+//! sleep-interval = 2
+//! follow = true
+//! ```
+
+use std::{
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// File the values were read from
+    pub path: PathBuf,
+    /// `(key, value)` pairs in file order
+    pub values: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Syntax(PathBuf, usize, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::Io(ref path, ref err) => write!(f, "{}: {}", path.display(), err),
+            ConfigError::Syntax(ref path, line, ref msg) => {
+                write!(f, "{}:{}: {}", path.display(), line, msg)
+            }
+        }
+    }
+}
+
+/// Returns `$XDG_CONFIG_HOME/rtail/config.toml`, falling back to
+/// `$HOME/.config/rtail/config.toml`.
+pub fn default_path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME").filter(|dir| !dir.is_empty())?).join(".config"),
+    };
+    Some(base.join("rtail").join("config.toml"))
+}
+
+impl Config {
+    /// Read the config file at `path`. A missing file is not an error.
+    pub fn load(path: &Path) -> Result<Option<Config>, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Config::parse(&text, path).map(Some),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(ConfigError::Io(path.to_path_buf(), err)),
+        }
+    }
+
+    pub fn parse(text: &str, path: &Path) -> Result<Config, ConfigError> {
+        let syntax =
+            |line: usize, msg: &str| ConfigError::Syntax(path.to_path_buf(), line, msg.to_string());
+        let mut values: Vec<(String, String)> = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                return Err(syntax(i + 1, "tables are not supported"));
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(syntax(i + 1, "expected 'key = value'")),
+            };
+            let key = unquote(key).unwrap_or(key);
+            if key.is_empty() {
+                return Err(syntax(i + 1, "missing key"));
+            }
+            if values.iter().any(|(k, _)| k == key) {
+                return Err(syntax(i + 1, &format!("duplicate key '{}'", key)));
+            }
+            let value = match unquote(value) {
+                Some(value) => value.to_string(),
+                None if is_bare_value(value) => value.to_string(),
+                None => return Err(syntax(i + 1, &format!("invalid value '{}'", value))),
+            };
+            values.push((key.to_string(), value));
+        }
+        Ok(Config {
+            path: path.to_path_buf(),
+            values,
+        })
+    }
+
+    #[allow(unused)]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Drop a `#` comment that is not inside a quoted string.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Returns the contents of a `"..."` or `'...'` string.
+fn unquote(s: &str) -> Option<&str> {
+    let quote = s.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    s.strip_prefix(quote)?.strip_suffix(quote)
+}
+
+/// Booleans and numbers may be written without quotes.
+fn is_bare_value(s: &str) -> bool {
+    s == "true" || s == "false" || (!s.is_empty() && s.parse::<f64>().is_ok())
+}
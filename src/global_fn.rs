@@ -1,25 +1,139 @@
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use std::{
     error::Error,
-    fs::{File, OpenOptions},
-    io::{stdin, BufRead, BufReader, Read, Seek, SeekFrom},
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, stdin, stdout, BufRead, BufReader, Read, Seek, SeekFrom, Write},
     path::Path,
-    process,
+    process, result, str,
     sync::mpsc::channel,
     time::Duration,
 };
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{
+    ansi_segments, truncate_to_width_ansi_cjk_for, truncate_to_width_ansi_for, AnsiSegment,
+    Profile, Truncated, UnicodeWidthStr, WidthOptions,
+};
 
-use crate::{arg_options::Options, optgroup::Name, result_error::Opt};
+use crate::{
+    arg_options::Options,
+    i18n::{tr, Locale, MsgId},
+    optgroup::{Constraint, Name, Occur},
+    result_error::{Fail, Opt, Optval},
+};
 
 const BUF_SIZE: usize = 1024;
 
-pub fn tail_file(path: &String, count: u64, fflag: bool) {
+/// Tab stops as terminals and the pager place them.
+pub const TABS: WidthOptions = WidthOptions {
+    tab_size: 8,
+    ..WidthOptions::new()
+};
+
+/// How lines are laid out for the terminal when they are printed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LineFormat {
+    /// Cut lines wider than this many columns, marking them with `…`.
+    pub chop: Option<usize>,
+    /// Wrap lines wider than this many columns at word boundaries.
+    pub wrap: Option<usize>,
+    /// Columns of spaces before each row a line is wrapped onto, at most
+    /// half of `wrap` so that the rows keep room for text.
+    pub wrap_indent: usize,
+    /// Measure text as in East Asian locales.
+    pub east_asian_width: bool,
+    /// How the terminal lays out emoji sequences.
+    pub profile: Profile,
+}
+
+impl LineFormat {
+    /// Apply the format to each line of `text`, keeping line endings.
+    pub fn apply(&self, text: &str) -> String {
+        if self.wrap.is_none() && self.chop.is_none() {
+            return text.to_string();
+        }
+        let mut out = String::with_capacity(text.len());
+        for line in text.split_inclusive('\n') {
+            let body = line.trim_end_matches(['\n', '\r']);
+            if let Some(max_width) = self.wrap {
+                let indent = self.wrap_indent.min(max_width / 2);
+                let fit = |s: &str, column, max| self.fit(s, column, max);
+                let rows = split_within(body, max_width, indent, true, &fit);
+                let sep = format!("\n{}", " ".repeat(indent));
+                out.push_str(&rows.join(&sep));
+            } else if let Some(max_width) = self.chop {
+                out.push_str(&self.chop(body, max_width));
+            }
+            out.push_str(&line[body.len()..]);
+        }
+        out
+    }
+
+    /// The width of `s` in columns, measured as lines are laid out.
+    pub fn width(&self, s: &str) -> usize {
+        if self.east_asian_width {
+            s.width_cjk_for(self.profile)
+        } else {
+            s.width_for(self.profile)
+        }
+    }
+
+    /// The length of the longest prefix of `s` that is at most `max` columns
+    /// wide when it starts at `column`, a tab reaching to the next tab stop.
+    pub fn fit(&self, s: &str, column: usize, max: usize) -> usize {
+        let end = column.saturating_add(max);
+        let mut column = column;
+        let mut len = 0;
+        for (i, part) in s.split('\t').enumerate() {
+            if i > 0 {
+                column += TABS.tab_size - column % TABS.tab_size;
+                if column > end {
+                    break;
+                }
+                len += 1;
+            }
+            let kept = self.truncate(part, end - column, None);
+            len += kept.text().len();
+            if kept.is_truncated() {
+                break;
+            }
+            column += kept.width();
+        }
+        len
+    }
+
+    /// `line` cut at `max_width` columns and marked with `…` if it is wider,
+    /// measured as by [`fit`](LineFormat::fit). The escape sequences of the
+    /// part cut off are kept, so that colours are still reset.
+    fn chop(&self, line: &str, max_width: usize) -> String {
+        if self.fit(line, 0, max_width) == line.len() {
+            return line.to_string();
+        }
+        let end = self.fit(line, 0, max_width.saturating_sub(self.width("…")));
+        let mut out = format!("{}…", &line[..end]);
+        for segment in ansi_segments(&line[end..]) {
+            if let AnsiSegment::Escape(escape) = segment {
+                out.push_str(escape);
+            }
+        }
+        out
+    }
+
+    fn truncate<'a>(&self, s: &'a str, max_width: usize, suffix: Option<&'a str>) -> Truncated<'a> {
+        let truncate = if self.east_asian_width {
+            truncate_to_width_ansi_cjk_for
+        } else {
+            truncate_to_width_ansi_for
+        };
+        truncate(s, max_width, suffix, self.profile)
+    }
+}
+
+pub fn tail_file(path: &Path, count: u64, fflag: bool, interval: Duration, format: LineFormat) {
     //let file = match File::open(path){
     let file = match OpenOptions::new().read(true).open(path) {
         Err(why) => panic!(
             "Cannot open file! file:{} cause:{:?}",
-            path,
+            path.display(),
             Error::source(&why)
         ),
         Ok(file) => file,
@@ -34,78 +148,106 @@ pub fn tail_file(path: &String, count: u64, fflag: bool) {
         process::exit(0);
     }
     let mut reader = BufReader::new(file);
+    let buf_str = match read_tail(&mut reader, f_size, count) {
+        Err(why) => panic!("Cannot read file! file:{} cause:{:?}", path.display(), why),
+        Ok(text) => text,
+    };
+    print_result(format.apply(&buf_str));
+    if fflag {
+        if cfg!(target_os = "windows") {
+            println!();
+        }
+        if let Err(why) = tail_file_follow(&mut reader, path, f_size, interval, format) {
+            panic!(
+                "Cannot follow file! file:{:?} cause:{:?}",
+                reader.by_ref(),
+                Error::source(&why)
+            )
+        }
+    }
+}
 
+/// Reads the last `count` lines of the `f_size` bytes long file behind
+/// `reader`, leaving it at the end of the file. A newline at the very end
+/// ends the last line rather than starting another, and bytes that are not
+/// UTF-8 are replaced.
+pub fn read_tail(reader: &mut BufReader<File>, f_size: u64, count: u64) -> io::Result<String> {
+    let mut start = f_size;
     let mut line_count = 0;
-    // minus 2 byte for skip eof null byte.
-    let mut current_pos = f_size - 2;
-    let mut read_start = if (f_size - 2) > BUF_SIZE as u64 {
-        f_size - 2 - BUF_SIZE as u64
-    } else {
-        0
-    };
     let mut buf = [0; BUF_SIZE];
-    'outer: loop {
-        match reader.seek(SeekFrom::Start(read_start)) {
-            Err(why) => panic!(
-                "Cannot move offset! offset:{} cause:{:?}",
-                current_pos,
-                Error::source(&why)
-            ),
-            Ok(_) => current_pos,
-        };
-        let b = match reader.read(&mut buf) {
-            Err(why) => panic!(
-                "Cannot read offset byte! offset:{} cause:{:?}",
-                current_pos,
-                Error::source(&why)
-            ),
-            Ok(b) => b,
-        };
-        for i in 0..b {
-            if buf[b - (i + 1)] == 0xA {
+    'outer: while start > 0 && count > 0 {
+        let read_start = start.saturating_sub(BUF_SIZE as u64);
+        let len = (start - read_start) as usize;
+        reader.seek(SeekFrom::Start(read_start))?;
+        reader.read_exact(&mut buf[..len])?;
+        for i in (0..len).rev() {
+            let pos = read_start + i as u64;
+            if buf[i] == b'\n' && pos + 1 < f_size {
                 line_count += 1;
+                if line_count == count {
+                    start = pos + 1;
+                    break 'outer;
+                }
             }
-            // println!("{}, {}", line_count, i);
-            if line_count == count {
-                break 'outer;
-            }
-            current_pos -= 1;
-            //println!("{}", current_pos);
-            if current_pos == 0 {
-                current_pos = 0;
-                break 'outer;
-            }
-        }
-        read_start = if read_start > BUF_SIZE as u64 {
-            read_start - BUF_SIZE as u64
-        } else {
-            0
         }
+        start = read_start;
     }
-    //println!("last pos :{}", current_pos);
-    match reader.seek(SeekFrom::Start(current_pos)) {
+    reader.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    reader
+        .by_ref()
+        .take(f_size - start)
+        .read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Takes the text out of `bytes`, replacing what is not UTF-8, except for
+/// a character cut off at the end, which is left for the next read.
+pub fn take_utf8_lossy(bytes: &mut Vec<u8>) -> String {
+    let cut = (bytes.len().saturating_sub(3)..bytes.len())
+        .rev()
+        .find(|&i| bytes[i] & 0xC0 != 0x80)
+        .filter(|&i| matches!(str::from_utf8(&bytes[i..]), Err(e) if e.error_len().is_none()))
+        .unwrap_or(bytes.len());
+    let text = String::from_utf8_lossy(&bytes[..cut]).into_owned();
+    bytes.drain(..cut);
+    text
+}
+
+pub fn tail_file_bytes(path: &Path, count: u64, fflag: bool, interval: Duration) {
+    let file = match OpenOptions::new().read(true).open(path) {
         Err(why) => panic!(
-            "Cannot read offset byte! offset:{} cause:{:?}",
-            current_pos,
+            "Cannot open file! file:{} cause:{:?}",
+            path.display(),
             Error::source(&why)
         ),
-        Ok(_) => current_pos,
+        Ok(file) => file,
     };
-    let mut buf_str = String::new();
-    match reader.read_to_string(&mut buf_str) {
-        Err(why) => panic!(
+    let f_size = match file.metadata() {
+        Err(why) => panic!("Cannot read file metadata :{:?}", Error::source(&why)),
+        Ok(data) => data.len(),
+    };
+    let mut reader = BufReader::new(file);
+    let start_pos = f_size.saturating_sub(count);
+    if let Err(why) = reader.seek(SeekFrom::Start(start_pos)) {
+        panic!(
+            "Cannot move offset! offset:{} cause:{:?}",
+            start_pos,
+            Error::source(&why)
+        )
+    }
+    let mut buf = Vec::new();
+    if let Err(why) = reader.read_to_end(&mut buf) {
+        panic!(
             "Cannot read offset byte! offset:{} cause:{:?}",
-            current_pos,
+            start_pos,
             Error::source(&why)
-        ),
-        Ok(_) => current_pos,
-    };
-    print_result(buf_str);
+        )
+    }
+    print_bytes(&buf);
     if fflag {
-        if cfg!(target_os = "windows") {
-            println!();
-        }
-        if let Err(why) = tail_file_follow(&mut reader, path, f_size) {
+        let format = LineFormat::default();
+        if let Err(why) = tail_file_follow(&mut reader, path, f_size, interval, format) {
             panic!(
                 "Cannot follow file! file:{:?} cause:{:?}",
                 reader.by_ref(),
@@ -117,43 +259,53 @@ pub fn tail_file(path: &String, count: u64, fflag: bool) {
 
 fn tail_file_follow(
     reader: &mut BufReader<File>,
-    spath: &String,
+    path: &Path,
+    file_size: u64,
+    interval: Duration,
+    format: LineFormat,
+) -> notify::Result<()> {
+    follow_file(reader, path, file_size, interval, |text| {
+        print_result(format.apply(text));
+        true
+    })
+}
+
+/// Watches the file at `path` for data appended after its first
+/// `file_size` bytes, passing each read to `on_text` until it returns
+/// false.
+pub fn follow_file(
+    reader: &mut BufReader<File>,
+    path: &Path,
     file_size: u64,
+    interval: Duration,
+    mut on_text: impl FnMut(&str) -> bool,
 ) -> notify::Result<()> {
     let config = Config::default()
-        .with_poll_interval(Duration::from_secs(1))
+        .with_poll_interval(interval)
         .with_compare_contents(true);
 
     let (tx, rx) = channel();
     let mut watcher: RecommendedWatcher = Watcher::new(tx, config)?;
-    let path = Path::new(spath);
     watcher.watch(path, RecursiveMode::NonRecursive)?;
 
     let mut start_byte = file_size;
-    let mut buf_str = String::new();
+    let mut bytes = Vec::new();
     loop {
         match rx.recv() {
             Err(e) => println!("watch error: {:?}", e),
             Ok(_) => {
-                match reader.seek(SeekFrom::Start(start_byte)) {
-                    Err(why) => panic!(
-                        "Cannot move offset! offset:{} cause:{:?}",
-                        start_byte,
-                        Error::source(&why)
-                    ),
-                    Ok(_) => start_byte,
-                };
-                let read_byte = match reader.read_to_string(&mut buf_str) {
-                    Err(why) => panic!(
-                        "Cannot read offset byte! offset:{} cause:{:?}",
-                        start_byte,
-                        Error::source(&why)
-                    ),
-                    Ok(b) => b,
-                };
+                reader
+                    .seek(SeekFrom::Start(start_byte))
+                    .map_err(notify::Error::io)?;
+                let pending = bytes.len();
+                let read_byte = reader.read_to_end(&mut bytes).map_err(notify::Error::io)?;
                 start_byte += read_byte as u64;
-                print_result(buf_str.clone());
-                buf_str.clear();
+                if bytes.len() == pending {
+                    continue;
+                }
+                if !on_text(&take_utf8_lossy(&mut bytes)) {
+                    return Ok(());
+                }
             }
         }
     }
@@ -196,60 +348,342 @@ pub fn find_opt(opts: &[Opt], nm: &Name) -> Option<usize> {
     None
 }
 
-pub fn print_usage(program: &str, options: &Options) {
-    let brief = format!("Usage: {} [options] FILE", program);
+/// Returns whether the option `nm` was given at least once.
+fn opt_given(opts: &[Opt], vals: &[Vec<(usize, Optval)>], nm: &str) -> bool {
+    match find_opt(opts, &Name::from_str(nm)) {
+        Some(id) => !resolve_vals(&opts[id], &vals[id]).is_empty(),
+        None => panic!("No option '{}' defined", nm),
+    }
+}
+
+/// Returns the values of `opt` that count: only the last one by position
+/// for `Occur::LastWins`, and none if that one is `Optval::Negated`.
+pub fn resolve_vals(opt: &Opt, vals: &[(usize, Optval)]) -> Vec<(usize, Optval)> {
+    let vals = match opt.occur {
+        Occur::LastWins => vals
+            .iter()
+            .max_by_key(|(pos, _)| *pos)
+            .cloned()
+            .into_iter()
+            .collect(),
+        _ => vals.to_vec(),
+    };
+    vals.into_iter()
+        .filter(|(_, val)| !matches!(val, Optval::Negated))
+        .collect()
+}
+
+/// The option `nm` as it is written on the command line, with its short
+/// and long forms: `-c/--bytes`, `-c` or `--chop`.
+fn opt_label(opts: &[Opt], nm: &str) -> String {
+    let opt = match find_opt(opts, &Name::from_str(nm)) {
+        Some(id) => &opts[id],
+        None => panic!("No option '{}' defined", nm),
+    };
+    let mut forms: Vec<String> = std::iter::once(opt)
+        .chain(opt.aliases.iter())
+        .map(|opt| match opt.name {
+            Name::Long(ref long) => format!("--{}", long),
+            Name::Short(ch) => format!("-{}", ch),
+        })
+        .collect();
+    // Short form first
+    forms.sort_by_key(|form| form.starts_with("--"));
+    forms.join("/")
+}
+
+pub fn check_constraint(
+    constraint: &Constraint,
+    opts: &[Opt],
+    vals: &[Vec<(usize, Optval)>],
+) -> result::Result<(), Fail> {
+    let label = |nm: &str| opt_label(opts, nm);
+    match *constraint {
+        Constraint::ConflictsWith(ref a, ref b) => {
+            if opt_given(opts, vals, a) && opt_given(opts, vals, b) {
+                return Err(Fail::ConflictingOptions(label(a), label(b)));
+            }
+        }
+        Constraint::Requires(ref nm, ref req) => {
+            if opt_given(opts, vals, nm) && !opt_given(opts, vals, req) {
+                return Err(Fail::OptionRequires(label(nm), label(req)));
+            }
+        }
+        Constraint::OneOf(ref nms) => {
+            let given: Vec<&String> = nms.iter().filter(|nm| opt_given(opts, vals, nm)).collect();
+            match given.len() {
+                0 => return Err(Fail::OneOfMissing(nms.iter().map(|nm| label(nm)).collect())),
+                1 => {}
+                _ => return Err(Fail::ConflictingOptions(label(given[0]), label(given[1]))),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the number of single-character insertions, deletions,
+/// substitutions and adjacent transpositions needed to turn `a` into `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Three rows of the distance matrix: two rows back, previous and current.
+    let mut prev2: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur: Vec<usize> = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        cur[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                cur[j] = cur[j].min(prev2[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// The names `opt` can be given by, each with whether it turns the option
+/// off: its own and its aliases, and `no-<long>` for each long one if the
+/// option is `negatable`.
+fn opt_names(opt: &Opt, negatable: bool) -> Vec<(Name, bool)> {
+    let names: Vec<Name> = std::iter::once(opt)
+        .chain(opt.aliases.iter())
+        .map(|opt| opt.name.clone())
+        .collect();
+    let negations = names.iter().filter_map(|name| match *name {
+        Name::Long(ref long) if negatable => Some((Name::Long(format!("no-{}", long)), true)),
+        _ => None,
+    });
+    let mut all: Vec<(Name, bool)> = names.iter().map(|name| (name.clone(), false)).collect();
+    all.extend(negations);
+    all
+}
+
+/// Returns the registered option (or alias, or `--no-` form of a negatable
+/// flag) closest to the unrecognized option `nm`, written the way it is
+/// given on the command line. `negatable[id]` tells whether `opts[id]` is
+/// negatable. Options more than a third of the length of `nm` away are not
+/// suggested, and neither are ones that share no character with it.
+pub fn suggest_opt(opts: &[Opt], negatable: &[bool], nm: &str) -> Option<String> {
+    let len = nm.chars().count();
+    let limit = (len / 3).max(1).min(len.saturating_sub(1));
+    opts.iter()
+        .zip(negatable)
+        .flat_map(|(opt, negatable)| opt_names(opt, *negatable))
+        .map(|(name, _)| match name {
+            Name::Long(ref long) => (edit_distance(nm, long), format!("--{}", long)),
+            Name::Short(ch) => (edit_distance(nm, &ch.to_string()), format!("-{}", ch)),
+        })
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, suggestion)| suggestion)
+}
+
+/// Returns the options with a long name starting with `prefix`, as indices
+/// into `opts`, the long name that matched and whether it is the `no-`
+/// form of a negatable flag. `negatable[id]` tells whether `opts[id]` is
+/// negatable.
+pub fn find_opt_prefix(
+    opts: &[Opt],
+    negatable: &[bool],
+    prefix: &str,
+) -> Vec<(usize, String, bool)> {
+    let mut found = Vec::new();
+    for (id, (opt, negatable)) in opts.iter().zip(negatable).enumerate() {
+        let long = opt_names(opt, *negatable)
+            .into_iter()
+            .find_map(|(name, negated)| match name {
+                Name::Long(long) if long.starts_with(prefix) => Some((long, negated)),
+                _ => None,
+            });
+        if let Some((long, negated)) = long {
+            found.push((id, long, negated));
+        }
+    }
+    found
+}
+
+/// Split the contents of a response file into arguments the way a shell
+/// would: words are separated by whitespace, `'...'` and `"..."` quote
+/// whitespace, a backslash escapes the next character and a `#` at the
+/// start of a word comments out the rest of the line.
+///
+/// Returns `None` if a quote is not closed.
+pub fn split_args(text: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.peek() {
+            None => break,
+            Some('#') => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+                continue;
+            }
+            Some(_) => {}
+        }
+        let mut arg = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+            match c {
+                '\'' => loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => arg.push(c),
+                    }
+                },
+                '"' => loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            c @ ('"' | '\\') => arg.push(c),
+                            c => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                        },
+                        c => arg.push(c),
+                    }
+                },
+                '\\' => match chars.next() {
+                    Some('\n') | None => {}
+                    Some(c) => arg.push(c),
+                },
+                c => arg.push(c),
+            }
+        }
+        args.push(arg);
+    }
+    Some(args)
+}
+
+/// Replace each `@file` argument before `--` with the arguments read from
+/// `file`, see `split_args()`. Arguments read from a response file are not
+/// expanded again.
+pub fn expand_response_files(args: Vec<OsString>) -> result::Result<Vec<OsString>, Fail> {
+    let mut expanded = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            expanded.push(arg);
+            expanded.extend(args);
+            break;
+        }
+        match arg.to_str().and_then(|arg| arg.strip_prefix('@')) {
+            Some(path) if !path.is_empty() => {
+                let text = fs::read_to_string(path)
+                    .map_err(|why| Fail::BadResponseFile(path.to_string(), why.to_string()))?;
+                let words =
+                    split_args(&text).ok_or_else(|| Fail::UnterminatedQuote(path.to_string()))?;
+                expanded.extend(words.into_iter().map(OsString::from));
+            }
+            _ => expanded.push(arg),
+        }
+    }
+    Ok(expanded)
+}
+
+pub fn print_usage(program: &str, options: &Options, locale: Locale) {
+    let brief = tr(locale, MsgId::UsageBrief, &[program]);
     print!("{}", options.usage(&brief));
     process::exit(0);
 }
 
+/// Print `msg` as an error of `program` and exit with status 1.
+pub fn print_error(program: &str, msg: &str) -> ! {
+    eprintln!("{}: {}", program, msg);
+    process::exit(1);
+}
+
+#[allow(unused)]
 pub fn each_split_within(desc: &str, lim: usize) -> Vec<String> {
-    let mut rows = Vec::new();
-    for line in desc.trim().lines() {
-        let line_chars = line.chars().chain(Some(' '));
-        let words = line_chars
-            .fold((Vec::new(), 0, 0), |(mut words, a, z), c| {
-                let idx = z + c.len_utf8(); // Get the current byte offset
-
-                // If the char is whitespace, advance the word start and maybe push a word
-                if c.is_whitespace() {
-                    if a != z {
-                        words.push(&line[a..z]);
-                    }
-                    (words, idx, idx)
-                }
-                // If the char is not whitespace, continue, retaining the current
-                else {
-                    (words, a, idx)
-                }
-            })
-            .0;
+    each_split_within_by(desc, lim, UnicodeWidthStr::width_ansi)
+}
 
-        let mut row = String::new();
-        for word in words.iter() {
-            let sep = if !row.is_empty() { Some(" ") } else { None };
-            let width = row.width() + word.width() + sep.map(UnicodeWidthStr::width).unwrap_or(0);
+/// Like `each_split_within`, measuring the displayed width of text with
+/// `width` (e.g. `UnicodeWidthStr::width_ansi_cjk` in East Asian locales).
+pub fn each_split_within_by(desc: &str, lim: usize, width: fn(&str) -> usize) -> Vec<String> {
+    let fit = |s: &str, _, max| {
+        s.char_indices()
+            .map(|(i, c)| i + c.len_utf8())
+            .take_while(|&end| width(&s[..end]) <= max)
+            .last()
+            .unwrap_or(0)
+    };
+    desc.trim()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .flat_map(|line| split_within(line, lim, 0, false, &fit))
+        .map(str::to_string)
+        .collect()
+}
 
-            if width <= lim {
-                if let Some(sep) = sep {
-                    row.push_str(sep)
-                }
-                row.push_str(word);
-                continue;
-            }
-            if !row.is_empty() {
-                rows.push(row.clone());
-                row.clear();
+/// Split `line` into rows at most `lim` columns wide, breaking at
+/// whitespace. Words too wide for a row of their own are broken if
+/// `break_words` is set, and otherwise overflow their row.
+///
+/// `fit(s, column, max)` is the length in bytes of the longest prefix of `s`
+/// that is at most `max` columns wide when it starts at `column`, which
+/// matters for tabs, and can be cut off without splitting a character. Rows
+/// after the first start at column `indent` and are that much narrower,
+/// leaving room for a hanging indent. The text of each row is kept as it
+/// is, apart from the whitespace at the breaks.
+pub fn split_within<'a>(
+    line: &'a str,
+    lim: usize,
+    indent: usize,
+    break_words: bool,
+    fit: &dyn Fn(&str, usize, usize) -> usize,
+) -> Vec<&'a str> {
+    let mut rows = Vec::new();
+    let mut rest = line;
+    let mut column = 0;
+    let mut row_lim = lim;
+    loop {
+        let end = fit(rest, column, row_lim);
+        if end == rest.len() {
+            if !rest.is_empty() || rows.is_empty() {
+                rows.push(rest);
             }
-            row.push_str(word);
+            return rows;
         }
-        if !row.is_empty() {
-            rows.push(row);
+        // The last break within the row, if it has any text before it
+        let text_start = rest.len() - rest.trim_start().len();
+        let space = rest
+            .char_indices()
+            .take_while(|&(i, _)| i <= end)
+            .filter(|&(i, c)| i > text_start && c.is_whitespace())
+            .last();
+        let (row, next) = match space {
+            Some((i, _)) => (rest[..i].trim_end(), rest[i..].trim_start()),
+            // A word wider than the row, or a single character wider still
+            None => {
+                let end = if !break_words {
+                    rest[text_start..]
+                        .find(char::is_whitespace)
+                        .map_or(rest.len(), |i| text_start + i)
+                } else if end > 0 {
+                    end
+                } else {
+                    rest.chars().next().map_or(0, char::len_utf8)
+                };
+                (&rest[..end], rest[end..].trim_start())
+            }
+        };
+        rows.push(row);
+        rest = next;
+        column = indent;
+        row_lim = lim.saturating_sub(indent).max(1);
+        if rest.is_empty() {
+            return rows;
         }
     }
-    rows
 }
 
-pub fn tail_stdin(count: u64) {
+pub fn tail_stdin(count: u64, format: LineFormat) {
     let stdin = stdin();
     let mut line_strs: Vec<String> = Vec::new();
     for line in stdin.lock().lines() {
@@ -266,9 +700,25 @@ pub fn tail_stdin(count: u64) {
         result += &line_strs[n as usize][..];
         result += "\n";
     }
-    print_result(result);
+    print_result(format.apply(&result));
+}
+
+pub fn tail_stdin_bytes(count: u64) {
+    let mut buf = Vec::new();
+    if let Err(why) = stdin().lock().read_to_end(&mut buf) {
+        panic!("Cannot read strin! cause:{:?}", Error::source(&why))
+    }
+    let start = buf.len().saturating_sub(count as usize);
+    print_bytes(&buf[start..]);
 }
 
 fn print_result(disp_str: String) {
     print!("{}", disp_str);
 }
+
+fn print_bytes(disp_bytes: &[u8]) {
+    let mut out = stdout().lock();
+    if let Err(why) = out.write_all(disp_bytes).and_then(|_| out.flush()) {
+        panic!("Cannot write stdout! cause:{:?}", Error::source(&why))
+    }
+}
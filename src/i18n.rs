@@ -0,0 +1,257 @@
+//! Message catalogue for help and error text.
+//!
+//! Messages are looked up by `MsgId` for a `Locale`. Templates use `{0}`,
+//! `{1}`, ... for their arguments, see `fill`.
+
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Ko,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsgId {
+    ArgumentMissing,
+    UnrecognizedOption,
+    UnrecognizedOptionSuggestion,
+    OptionMissing,
+    OptionDuplicated,
+    UnexpectedArgument,
+    ConflictingOptions,
+    OptionRequires,
+    OneOfMissing,
+    UnknownSetting,
+    AmbiguousOption,
+    BadResponseFile,
+    UnterminatedQuote,
+    InvalidUtf8Argument,
+    UsageBrief,
+    SectionOutput,
+    SectionMisc,
+    HelpLines,
+    HelpBytes,
+    HelpFollow,
+    HelpHelp,
+    HelpCompletions,
+    HelpDocs,
+    HelpSleepInterval,
+    HelpShowConfig,
+    HelpChop,
+    HelpWrap,
+    HelpWrapIndent,
+    HelpAmbiguousWidth,
+    HelpTerminal,
+    HelpInteractive,
+    HelpScrollback,
+    BadLineCount,
+    BadByteCount,
+    UnsupportedShell,
+    UnsupportedFormat,
+    HelpHint,
+    BadSleepInterval,
+    BadAmbiguousWidth,
+    BadWrapIndent,
+    BadTerminal,
+    BadScrollback,
+    InteractiveNeedsFile,
+    PagerFailed,
+    PagerFollowing,
+    PagerPaused,
+    PagerStopped,
+    PagerRate,
+    PagerNotFound,
+    BadConfig,
+    SourceCommandLine,
+    SourceEnv,
+    SourceDefault,
+}
+
+impl Locale {
+    /// Parse a POSIX locale name such as `ko_KR.UTF-8`. Unknown languages
+    /// fall back to English.
+    pub fn from_name(name: &str) -> Locale {
+        let lang = name.split(['_', '.', '@']).next().unwrap_or("");
+        match lang {
+            "ko" => Locale::Ko,
+            _ => Locale::En,
+        }
+    }
+
+    /// The locale for messages, taken from the first of `LC_ALL`,
+    /// `LC_MESSAGES` and `LANG` that is set.
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map_or(Locale::En, |value| Locale::from_name(&value))
+    }
+
+    /// Whether text in this locale should be measured with East Asian
+    /// (`width_cjk`) widths.
+    pub fn is_east_asian(self) -> bool {
+        match self {
+            Locale::En => false,
+            Locale::Ko => true,
+        }
+    }
+}
+
+/// Returns the message template for `id` in `locale`.
+pub fn message(locale: Locale, id: MsgId) -> &'static str {
+    use MsgId::*;
+    match locale {
+        Locale::En => match id {
+            ArgumentMissing => "Argument to option '{0}' missing",
+            UnrecognizedOption => "Unrecognized option: '{0}'",
+            UnrecognizedOptionSuggestion => "Unrecognized option '{0}'; did you mean '{1}'?",
+            OptionMissing => "Required option '{0}' missing",
+            OptionDuplicated => "Option '{0}' given more than once",
+            UnexpectedArgument => "Option '{0}' does not take an argument",
+            ConflictingOptions => "Options '{0}' and '{1}' cannot be used together",
+            OptionRequires => "Option '{0}' requires option '{1}'",
+            OneOfMissing => "One of the options '{0}' is required",
+            UnknownSetting => "Unknown setting in {0}: '{1}'",
+            AmbiguousOption => "Option '{0}' is ambiguous; possibilities: '{1}'",
+            BadResponseFile => "Cannot read response file '{0}': {1}",
+            UnterminatedQuote => "Unterminated quote in response file '{0}'",
+            InvalidUtf8Argument => "Argument to option '{0}' is not valid UTF-8",
+            UsageBrief => "Usage: {0} [options] FILE",
+            SectionOutput => "Output",
+            SectionMisc => "Misc",
+            HelpLines => "number of lines",
+            HelpBytes => "number of bytes",
+            HelpFollow => "output appended data as the file grows",
+            HelpHelp => "print help, or the option schema with FORMAT=json",
+            HelpCompletions => "print a completion script for SHELL (bash, zsh or fish)",
+            HelpDocs => "print the reference page as FORMAT (man or markdown)",
+            HelpSleepInterval => "with -f, check the file every N seconds (default 1)",
+            HelpShowConfig => "print the effective settings and where they come from",
+            HelpChop => "cut lines at the terminal edge, marking them with …",
+            HelpWrap => "wrap lines at the terminal edge, between words",
+            HelpWrapIndent => "indent the rows of a wrapped line after the first by N columns",
+            HelpAmbiguousWidth => {
+                "columns for ambiguous width characters: narrow, wide or auto (from the locale)"
+            }
+            HelpTerminal => {
+                "lay out emoji like this terminal: unicode (most terminals), xterm, vte, \
+                 kitty, wezterm, windows-terminal or auto (detected)"
+            }
+            HelpInteractive => {
+                "browse the file full screen, with scrollback and search, following it as it grows"
+            }
+            HelpScrollback => "with -i, keep the last N lines (default 10000)",
+            BadLineCount => "invalid number of lines: '{0}'",
+            BadByteCount => "invalid number of bytes: '{0}'",
+            UnsupportedShell => "unsupported shell: '{0}'",
+            UnsupportedFormat => "unsupported format: '{0}'",
+            HelpHint => "Try '{0} --help' for more information.",
+            BadSleepInterval => "invalid number of seconds: '{0}'",
+            BadAmbiguousWidth => "invalid ambiguous width: '{0}' (expected narrow, wide or auto)",
+            BadWrapIndent => "invalid number of columns: '{0}'",
+            BadTerminal => "unknown terminal: '{0}'",
+            BadScrollback => "invalid number of lines: '{0}'",
+            InteractiveNeedsFile => "option '-i' needs a FILE",
+            PagerFailed => "cannot start the pager: {0}",
+            PagerFollowing => "following",
+            PagerPaused => "paused",
+            PagerStopped => "stopped: {0}",
+            PagerRate => "{0} lines/s",
+            PagerNotFound => "not found",
+            BadConfig => "cannot read config file {0}",
+            SourceCommandLine => "command line",
+            SourceEnv => "environment variable {0}",
+            SourceDefault => "default",
+        },
+        Locale::Ko => match id {
+            ArgumentMissing => "옵션 '{0}'에 인자가 없습니다",
+            UnrecognizedOption => "알 수 없는 옵션: '{0}'",
+            UnrecognizedOptionSuggestion => "알 수 없는 옵션 '{0}'; '{1}'을(를) 찾으시나요?",
+            OptionMissing => "필수 옵션 '{0}'이(가) 없습니다",
+            OptionDuplicated => "옵션 '{0}'이(가) 두 번 이상 주어졌습니다",
+            UnexpectedArgument => "옵션 '{0}'은(는) 인자를 받지 않습니다",
+            ConflictingOptions => "옵션 '{0}'와(과) '{1}'은(는) 함께 쓸 수 없습니다",
+            OptionRequires => "옵션 '{0}'에는 옵션 '{1}'이(가) 필요합니다",
+            OneOfMissing => "옵션 '{0}' 중 하나가 필요합니다",
+            UnknownSetting => "{0}에 알 수 없는 설정이 있습니다: '{1}'",
+            AmbiguousOption => "옵션 '{0}'이(가) 모호합니다. 가능한 옵션: '{1}'",
+            BadResponseFile => "응답 파일 '{0}'을(를) 읽을 수 없습니다: {1}",
+            UnterminatedQuote => "응답 파일 '{0}'에 닫히지 않은 따옴표가 있습니다",
+            InvalidUtf8Argument => "옵션 '{0}'의 인자가 올바른 UTF-8이 아닙니다",
+            UsageBrief => "사용법: {0} [옵션] FILE",
+            SectionOutput => "출력",
+            SectionMisc => "기타",
+            HelpLines => "출력할 줄 수",
+            HelpBytes => "출력할 바이트 수",
+            HelpFollow => "파일이 커지면 추가된 내용을 계속 출력",
+            HelpHelp => "도움말 출력, FORMAT=json이면 옵션 스키마 출력",
+            HelpCompletions => "SHELL(bash, zsh, fish)용 자동 완성 스크립트 출력",
+            HelpDocs => "참조 문서를 FORMAT(man, markdown) 형식으로 출력",
+            HelpSleepInterval => "-f 사용 시 N초마다 파일 확인 (기본값 1)",
+            HelpShowConfig => "적용된 설정과 그 출처를 출력",
+            HelpChop => "터미널 폭을 넘는 줄을 잘라 …로 표시",
+            HelpWrap => "터미널 폭을 넘는 줄을 단어 사이에서 줄바꿈",
+            HelpWrapIndent => "줄바꿈된 줄의 두 번째 행부터 N칸 들여쓰기",
+            HelpAmbiguousWidth => "모호한 폭 문자의 칸 수: narrow, wide 또는 auto (로케일 기준)",
+            HelpTerminal => {
+                "이모지를 배치할 터미널: unicode (대부분의 터미널), xterm, vte, \
+                 kitty, wezterm, windows-terminal 또는 auto (자동 감지)"
+            }
+            HelpInteractive => "전체 화면에서 스크롤과 검색을 하며 커지는 파일을 계속 보기",
+            HelpScrollback => "-i 사용 시 최근 N줄까지 보관 (기본값 10000)",
+            BadLineCount => "잘못된 줄 수: '{0}'",
+            BadByteCount => "잘못된 바이트 수: '{0}'",
+            UnsupportedShell => "지원하지 않는 셸: '{0}'",
+            UnsupportedFormat => "지원하지 않는 형식: '{0}'",
+            HelpHint => "자세한 내용은 '{0} --help'를 실행하세요.",
+            BadSleepInterval => "잘못된 초 단위 값: '{0}'",
+            BadAmbiguousWidth => "잘못된 모호한 폭 값: '{0}' (narrow, wide 또는 auto)",
+            BadWrapIndent => "잘못된 칸 수: '{0}'",
+            BadTerminal => "알 수 없는 터미널: '{0}'",
+            BadScrollback => "잘못된 줄 수: '{0}'",
+            InteractiveNeedsFile => "옵션 '-i'에는 FILE이 필요합니다",
+            PagerFailed => "페이저를 시작할 수 없습니다: {0}",
+            PagerFollowing => "따라가는 중",
+            PagerPaused => "일시 정지",
+            PagerStopped => "중지됨: {0}",
+            PagerRate => "초당 {0}줄",
+            PagerNotFound => "찾을 수 없음",
+            BadConfig => "설정 파일을 읽을 수 없습니다: {0}",
+            SourceCommandLine => "명령줄",
+            SourceEnv => "환경 변수 {0}",
+            SourceDefault => "기본값",
+        },
+    }
+}
+
+/// Replace the `{0}`, `{1}`, ... placeholders of `template` with `args`.
+pub fn fill(template: &str, args: &[&str]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let arg = after
+            .find('}')
+            .and_then(|end| Some((args.get(after[..end].parse::<usize>().ok()?)?, end)));
+        match arg {
+            Some((arg, end)) => {
+                out.push_str(arg);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Look up `id` in `locale` and fill in its placeholders.
+pub fn tr(locale: Locale, id: MsgId, args: &[&str]) -> String {
+    fill(message(locale, id), args)
+}
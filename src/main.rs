@@ -1,48 +1,311 @@
-use std::env;
+use std::{env, ffi::OsString, path::Path, time::Duration};
 
 use arg_options::Options;
-use global_fn::{print_usage, tail_file, tail_stdin};
+use completion::Shell;
+use config::Config;
+use global_fn::{
+    print_error, print_usage, tail_file, tail_file_bytes, tail_stdin, tail_stdin_bytes, LineFormat,
+};
+use i18n::{message, tr, Locale, MsgId};
+use manpage::Page;
+use result_error::{Matches, Source};
+use unicode_width::{AmbiguousWidth, Profile};
 mod arg_options;
+mod completion;
+mod config;
 mod global_fn;
+mod i18n;
+mod manpage;
 mod optgroup;
+mod pager;
 mod result_error;
+mod schema;
+mod terminal;
 
 #[allow(unused)]
 mod tests;
 
+const EXAMPLES: &[(&str, &str)] = &[
+    ("rtail -n 20 app.log", "Print the last 20 lines of app.log."),
+    (
+        "rtail -f app.log",
+        "Print the last 10 lines of app.log, then keep printing lines as they are appended.",
+    ),
+    (
+        "rtail -i -n 1000 app.log",
+        "Browse the last 1000 lines of app.log full screen, following it as it grows.",
+    ),
+    (
+        "dmesg | rtail -c 512",
+        "Print the last 512 bytes of the standard input.",
+    ),
+];
+
+fn rtail_options(locale: Locale) -> Options {
+    let msg = |id| message(locale, id);
+    let mut options = Options::new();
+    options.east_asian_width(locale.is_east_asian());
+    options.section(msg(MsgId::SectionOutput));
+    options.optopt("n", "lines", msg(MsgId::HelpLines), "NUMS");
+    options.optopt("c", "bytes", msg(MsgId::HelpBytes), "NUMS");
+    options.optflag_negatable("f", "follow", msg(MsgId::HelpFollow));
+    options.optopt("s", "sleep-interval", msg(MsgId::HelpSleepInterval), "N");
+    options.optflag("", "chop", msg(MsgId::HelpChop));
+    options.optflag("", "wrap", msg(MsgId::HelpWrap));
+    options.optopt("", "wrap-indent", msg(MsgId::HelpWrapIndent), "N");
+    options.optopt(
+        "",
+        "ambiguous-width",
+        msg(MsgId::HelpAmbiguousWidth),
+        "WHEN",
+    );
+    options.optopt("", "terminal", msg(MsgId::HelpTerminal), "NAME");
+    options.optflag("i", "interactive", msg(MsgId::HelpInteractive));
+    options.optopt("", "scrollback", msg(MsgId::HelpScrollback), "N");
+    options.section(msg(MsgId::SectionMisc));
+    options.optflagopt("h", "help", msg(MsgId::HelpHelp), "FORMAT");
+    options.optflag("", "show-config", msg(MsgId::HelpShowConfig));
+    options.optopt(
+        "",
+        "generate-completions",
+        msg(MsgId::HelpCompletions),
+        "SHELL",
+    );
+    options.optopt("", "generate-docs", msg(MsgId::HelpDocs), "FORMAT");
+    options.conflicts_with("c", "n");
+    options.conflicts_with("chop", "wrap");
+    options.conflicts_with("i", "c");
+    options.last_wins("n");
+    options.last_wins("c");
+    options.last_wins("s");
+    options.last_wins("wrap-indent");
+    options.last_wins("ambiguous-width");
+    options.last_wins("terminal");
+    options.last_wins("scrollback");
+    options.long_prefixes(true);
+    options.response_files(true);
+    options.default_value("lines", "10");
+    options.default_value("follow", "false");
+    options.default_value("sleep-interval", "1");
+    options.default_value("wrap-indent", "0");
+    options.default_value("ambiguous-width", "auto");
+    options.default_value("terminal", "auto");
+    options.default_value("scrollback", "10000");
+    options.env_var("lines", "RTAIL_LINES");
+    options.env_var("bytes", "RTAIL_BYTES");
+    options.env_var("follow", "RTAIL_FOLLOW");
+    options.env_var("sleep-interval", "RTAIL_SLEEP_INTERVAL");
+    options
+}
+
+/// Print the settings `show-config` explains, each with where its value
+/// came from.
+fn show_config(options: &Options, matches: &Matches, locale: Locale) {
+    for key in ["lines", "bytes", "follow", "sleep-interval"] {
+        // The line count does not apply when a byte count is set.
+        let default = match key {
+            "lines" if matches.opt_present("bytes") => None,
+            _ => options.get_default(key),
+        };
+        let value = match matches.opt_source(key) {
+            Some(_) if matches.opt_present(key) => {
+                matches.opt_str(key).unwrap_or_else(|| "true".to_string())
+            }
+            // Turned off with `--no-<key>`
+            Some(_) => "false".to_string(),
+            None => match default {
+                Some(value) => value.to_string(),
+                None => continue,
+            },
+        };
+        let source = match matches.opt_source(key) {
+            Some(Source::CommandLine) => message(locale, MsgId::SourceCommandLine).to_string(),
+            Some(Source::Env(var)) => tr(locale, MsgId::SourceEnv, &[&var]),
+            Some(Source::Config(path)) => path.display().to_string(),
+            None => message(locale, MsgId::SourceDefault).to_string(),
+        };
+        println!("{} = {} ({})", key, value, source);
+    }
+}
+
+fn rtail_page(name: &str) -> Page<'_> {
+    Page {
+        name,
+        about: "output the last part of files",
+        synopsis: "[options] FILE",
+        examples: EXAMPLES,
+    }
+}
+
 #[allow(unused)]
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let program = args[0].clone();
-    let mut options = Options::new();
-    options.optopt("n", "", "number of lines", "NUMS");
-    options.optflag("f", "-follow", "output appended data as the file grows");
-    options.optflag("h", "", "print help");
+    let args: Vec<OsString> = env::args_os().collect();
+    let program = args[0].to_string_lossy().into_owned();
+    let locale = Locale::from_env();
+    let mut options = rtail_options(locale);
 
-    let cmd_args = match options.parse(&args[1..]) {
-        Ok(ok) => ok,
-        Err(e) => panic!("Cannot parse command args : {:?}", e),
+    let config = match config::default_path() {
+        Some(path) => match Config::load(&path) {
+            Ok(config) => config,
+            Err(e) => print_error(&program, &tr(locale, MsgId::BadConfig, &[&e.to_string()])),
+        },
+        None => None,
+    };
+
+    let cmd_args =
+        match options.parse_with_defaults(&args[1..], |var| env::var(var).ok(), config.as_ref()) {
+            Ok(ok) => ok,
+            Err(e) => print_error(
+                &program,
+                &format!(
+                    "{}\n{}",
+                    e.message(locale),
+                    tr(locale, MsgId::HelpHint, &[&program])
+                ),
+            ),
+        };
+
+    let ambiguous_width = match cmd_args.opt_str("ambiguous-width").as_deref() {
+        None | Some("auto") => AmbiguousWidth::Auto,
+        Some("narrow") => AmbiguousWidth::Narrow,
+        Some("wide") => AmbiguousWidth::Wide,
+        Some(value) => print_error(&program, &tr(locale, MsgId::BadAmbiguousWidth, &[value])),
+    };
+    let east_asian_width = ambiguous_width.resolve() == AmbiguousWidth::Wide;
+    options.east_asian_width(east_asian_width);
+
+    let profile = match cmd_args.opt_str("terminal").as_deref() {
+        None | Some("auto") => Profile::Auto,
+        Some("unicode") => Profile::Unicode,
+        Some("xterm") => Profile::Xterm,
+        Some("vte") => Profile::Vte,
+        Some("kitty") => Profile::Kitty,
+        Some("wezterm") => Profile::WezTerm,
+        Some("windows-terminal") => Profile::WindowsTerminal,
+        Some(value) => print_error(&program, &tr(locale, MsgId::BadTerminal, &[value])),
     };
 
     if cmd_args.opt_present("h") {
-        print_usage(&program, &options);
+        match cmd_args.opt_str("h").as_deref() {
+            None => {
+                if let Some(width) = terminal::terminal_width() {
+                    options.term_width(width);
+                }
+                print_usage(&program, &options, locale);
+            }
+            Some("json") => print!("{}", options.json_schema()),
+            Some(format) => print_error(&program, &tr(locale, MsgId::UnsupportedFormat, &[format])),
+        }
+        return;
+    }
+
+    let name = Path::new(&program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("rtail");
+
+    if let Some(shell) = cmd_args.opt_str("generate-completions") {
+        let shell = match Shell::from_str(&shell) {
+            Some(shell) => shell,
+            None => print_error(&program, &tr(locale, MsgId::UnsupportedShell, &[&shell])),
+        };
+        print!("{}", completion::generate(shell, name, &options));
+        return;
+    }
+
+    if let Some(format) = cmd_args.opt_str("generate-docs") {
+        match format.as_str() {
+            "man" => print!("{}", options.man_page(&rtail_page(name))),
+            "markdown" => print!("{}", options.markdown(&rtail_page(name))),
+            _ => print_error(&program, &tr(locale, MsgId::UnsupportedFormat, &[&format])),
+        }
+        return;
+    }
+
+    if cmd_args.opt_present("show-config") {
+        show_config(&options, &cmd_args, locale);
+        return;
+    }
+
+    let fflag = cmd_args.opt_present("f");
+    let interval = match cmd_args.opt_str("s") {
+        Some(secs) => match secs.trim().parse::<f64>() {
+            Ok(secs) if secs.is_finite() && secs >= 0.0 => Duration::from_secs_f64(secs),
+            _ => print_error(&program, &tr(locale, MsgId::BadSleepInterval, &[&secs])),
+        },
+        None => Duration::from_secs(1),
+    };
+
+    if let Some(str_num) = cmd_args.opt_str("c") {
+        let byte_count = match str_num.trim().parse() {
+            Ok(num) => num,
+            Err(_) => print_error(&program, &tr(locale, MsgId::BadByteCount, &[&str_num])),
+        };
+        if let Some(file) = cmd_args.free_os.first() {
+            tail_file_bytes(Path::new(file), byte_count, fflag, interval);
+        } else {
+            tail_stdin_bytes(byte_count);
+        }
         return;
     }
 
     let line_number = if let Some(str_num) = cmd_args.opt_str("n") {
         match str_num.trim().parse() {
             Ok(num) => num,
-            Err(_) => panic!("specify line number!"),
+            Err(_) => print_error(&program, &tr(locale, MsgId::BadLineCount, &[&str_num])),
         }
     } else {
         10
     };
 
-    let fflag = cmd_args.opt_present("f");
+    let wrap_indent = match cmd_args.opt_str("wrap-indent") {
+        Some(cols) => match cols.trim().parse() {
+            Ok(cols) => cols,
+            Err(_) => print_error(&program, &tr(locale, MsgId::BadWrapIndent, &[&cols])),
+        },
+        None => 0,
+    };
+
+    let format = LineFormat {
+        chop: if cmd_args.opt_present("chop") {
+            terminal::terminal_width()
+        } else {
+            None
+        },
+        wrap: if cmd_args.opt_present("wrap") {
+            terminal::terminal_width()
+        } else {
+            None
+        },
+        wrap_indent,
+        east_asian_width,
+        profile: profile.resolve(),
+    };
+
+    if cmd_args.opt_present("i") {
+        let Some(file) = cmd_args.free_os.first() else {
+            print_error(&program, message(locale, MsgId::InteractiveNeedsFile));
+        };
+        let scrollback = match cmd_args.opt_str("scrollback") {
+            Some(str_num) => match str_num.trim().parse() {
+                Ok(num) => num,
+                Err(_) => print_error(&program, &tr(locale, MsgId::BadScrollback, &[&str_num])),
+            },
+            None => 10000,
+        };
+        let path = Path::new(file);
+        if let Err(why) = pager::run(path, line_number, scrollback, interval, format, locale) {
+            print_error(
+                &program,
+                &tr(locale, MsgId::PagerFailed, &[&why.to_string()]),
+            );
+        }
+        return;
+    }
 
-    if let Some(file) = cmd_args.free.first() {
-        tail_file(file, line_number, fflag);
+    if let Some(file) = cmd_args.free_os.first() {
+        tail_file(Path::new(file), line_number, fflag, interval, format);
     } else {
-        tail_stdin(line_number);
+        tail_stdin(line_number, format);
     }
 }
@@ -0,0 +1,130 @@
+use crate::{
+    arg_options::Options,
+    optgroup::{HasArg, OptGroup},
+};
+
+/// The parts of a reference page that are not derived from the options.
+pub struct Page<'a> {
+    /// Command name, e.g. `"rtail"`
+    pub name: &'a str,
+    /// One-line summary shown in the NAME section
+    pub about: &'a str,
+    /// Arguments following the command name in the SYNOPSIS section
+    pub synopsis: &'a str,
+    /// Example command lines and what they do
+    pub examples: &'a [(&'a str, &'a str)],
+}
+
+/// Escape text for use in a roff document.
+fn roff_escape(s: &str) -> String {
+    let escaped = s.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+fn roff_tag(grp: &OptGroup) -> String {
+    let mut names = Vec::new();
+    if !grp.short_name.is_empty() {
+        names.push(format!("\\fB\\-{}\\fR", roff_escape(&grp.short_name)));
+    }
+    if !grp.long_name.is_empty() {
+        names.push(format!("\\fB\\-\\-{}\\fR", roff_escape(&grp.long_name)));
+    }
+    if grp.negatable {
+        names.push(format!(
+            "\\fB\\-\\-no\\-{}\\fR",
+            roff_escape(&grp.long_name)
+        ));
+    }
+    let mut tag = names.join(", ");
+    match grp.hasarg {
+        HasArg::No => {}
+        HasArg::Yes => tag.push_str(&format!(" \\fI{}\\fR", roff_escape(&grp.hint))),
+        HasArg::Maybe => tag.push_str(&format!("[=\\fI{}\\fR]", roff_escape(&grp.hint))),
+    }
+    tag
+}
+
+impl Options {
+    /// Render a roff man page for section 1 of the manual.
+    pub fn man_page(&self, page: &Page) -> String {
+        let mut out = format!(".TH {} 1\n", page.name.to_uppercase());
+        out.push_str(&format!(
+            ".SH NAME\n{} \\- {}\n",
+            roff_escape(page.name),
+            roff_escape(page.about)
+        ));
+        out.push_str(&format!(
+            ".SH SYNOPSIS\n.B {}\n{}\n",
+            roff_escape(page.name),
+            roff_escape(page.synopsis)
+        ));
+
+        out.push_str(".SH OPTIONS\n");
+        let mut section = None;
+        for grp in self.grps.iter() {
+            if let Some(ref title) = grp.section {
+                if section != Some(title) {
+                    out.push_str(&format!(".SS {}\n", roff_escape(title)));
+                }
+            }
+            section = grp.section.as_ref();
+            let desc = grp
+                .desc
+                .lines()
+                .map(|line| roff_escape(line.trim()))
+                .collect::<Vec<String>>()
+                .join("\n");
+            out.push_str(&format!(".TP\n{}\n{}\n", roff_tag(grp), desc));
+        }
+
+        if !self.subcommands.is_empty() {
+            out.push_str(".SH COMMANDS\n");
+            for sub in self.subcommands.iter() {
+                out.push_str(&format!(
+                    ".TP\n\\fB{}\\fR\n{}\n",
+                    roff_escape(&sub.name),
+                    roff_escape(&sub.desc)
+                ));
+            }
+        }
+
+        if !page.examples.is_empty() {
+            out.push_str(".SH EXAMPLES\n");
+            for (cmd, what) in page.examples.iter() {
+                out.push_str(&format!(
+                    ".TP\n\\fB{}\\fR\n{}\n",
+                    roff_escape(cmd),
+                    roff_escape(what)
+                ));
+            }
+        }
+        out
+    }
+
+    /// Render a Markdown reference. The OPTIONS section is the same text that
+    /// `usage()` prints.
+    pub fn markdown(&self, page: &Page) -> String {
+        let mut out = format!("# {}\n\n", page.name);
+        out.push_str(&format!("## NAME\n\n{} - {}\n\n", page.name, page.about));
+        out.push_str(&format!(
+            "## SYNOPSIS\n\n```\n{} {}\n```\n\n",
+            page.name, page.synopsis
+        ));
+        out.push_str(&format!(
+            "## OPTIONS\n\n```\n{}\n```\n",
+            self.usage_sections()
+        ));
+
+        if !page.examples.is_empty() {
+            out.push_str("\n## EXAMPLES\n");
+            for (cmd, what) in page.examples.iter() {
+                out.push_str(&format!("\n```bash\n$ {}\n```\n\n{}\n", cmd, what));
+            }
+        }
+        out
+    }
+}
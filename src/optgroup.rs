@@ -10,6 +10,9 @@ pub struct OptGroup {
     pub desc: String,
     pub hasarg: HasArg,
     pub occur: Occur,
+    pub section: Option<String>,
+    /// Whether `--no-<long_name>` turns the flag off again
+    pub negatable: bool,
 }
 
 #[allow(unused)]
@@ -25,6 +28,19 @@ pub enum Occur {
     Req,
     Optional,
     Multi,
+    /// May be given more than once, the last occurrence counts
+    LastWins,
+}
+
+/// A rule between options that is checked once all arguments are parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    /// The two options may not be given together.
+    ConflictsWith(String, String),
+    /// The first option may only be given together with the second.
+    Requires(String, String),
+    /// Exactly one of the options must be given.
+    OneOf(Vec<String>),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -114,6 +130,8 @@ impl OptGroup {
             desc: self.desc.clone(),
             hasarg: self.hasarg.clone(),
             occur: self.occur.clone(),
+            section: self.section.clone(),
+            negatable: self.negatable,
         }
     }
 }
@@ -0,0 +1,767 @@
+//! The full-screen pager of `rtail -i`.
+//!
+//! The pager keeps the last lines of a file, follows it as it grows and
+//! lets the user scroll back and search. Keys:
+//!
+//! | Key              | Action                                           |
+//! |------------------|--------------------------------------------------|
+//! | `j`, Down, Enter | scroll down a line                               |
+//! | `k`, Up          | scroll up a line                                 |
+//! | Space, Page Down | scroll down a page                               |
+//! | `b`, Page Up     | scroll up a page                                 |
+//! | `g`, Home        | jump to the first line                           |
+//! | `G`, End         | jump to the last line                            |
+//! | `f`              | pause or resume following the file               |
+//! | `/`              | search as you type, Enter to keep, Esc to cancel |
+//! | `n`, `N`         | next or previous match                           |
+//! | `q`, Ctrl-C      | quit                                             |
+//!
+//! Scrolling up pauses following, so new lines don't move the view.
+//! [`Pager`] only keeps the state and draws into a [`Screen`]; [`run`]
+//! connects it to the terminal.
+
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    io::{self, Write},
+    path::Path,
+    sync::mpsc::Receiver,
+    time::{Duration, Instant},
+};
+
+use unicode_width::{ansi_segments, AnsiSegment, UnicodeWidthStr};
+
+use crate::{
+    global_fn::{LineFormat, TABS},
+    i18n::{message, tr, Locale, MsgId},
+    terminal,
+};
+
+/// How far back the line rate in the status line looks.
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// A key read from the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Esc,
+    Backspace,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    CtrlC,
+}
+
+/// Splits the bytes of one read from the terminal into keys. Escape
+/// sequences for keys the pager doesn't use are dropped.
+pub fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut chars = text.chars().peekable();
+    let mut keys = Vec::new();
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' if matches!(chars.peek(), Some('[' | 'O')) => {
+                chars.next();
+                let mut seq = String::new();
+                for c in chars.by_ref() {
+                    seq.push(c);
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+                match seq.as_str() {
+                    "A" => Key::Up,
+                    "B" => Key::Down,
+                    "H" | "1~" | "7~" => Key::Home,
+                    "F" | "4~" | "8~" => Key::End,
+                    "5~" => Key::PageUp,
+                    "6~" => Key::PageDown,
+                    _ => continue,
+                }
+            }
+            '\x1b' => Key::Esc,
+            '\r' | '\n' => Key::Enter,
+            '\x7f' | '\x08' => Key::Backspace,
+            '\x03' => Key::CtrlC,
+            c if c.is_control() => continue,
+            c => Key::Char(c),
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+/// A grid of terminal cells that the pager draws into, then written to
+/// the terminal in one go.
+///
+/// Each cell holds the text shown in it: a wide character is followed by
+/// an empty cell, and colour escape sequences are kept with the text after
+/// them, so the grid can be checked without a terminal.
+pub struct Screen {
+    cols: usize,
+    rows: usize,
+    cells: Vec<String>,
+    reverse: Vec<bool>,
+}
+
+impl Screen {
+    /// A blank screen of `cols` columns and `rows` rows.
+    pub fn new(cols: usize, rows: usize) -> Screen {
+        Screen {
+            cols,
+            rows,
+            cells: vec![" ".to_string(); cols * rows],
+            reverse: vec![false; rows],
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Draws `text` on `row` from its first column, measured as `format`
+    /// measures it and cut at the right edge.
+    ///
+    /// Tabs are expanded and other control characters shown as `�`. Escape
+    /// sequences other than colours (SGR) are dropped, since they would
+    /// move the cursor.
+    pub fn draw(&mut self, row: usize, text: &str, format: &LineFormat) {
+        let cells = &mut self.cells[row * self.cols..(row + 1) * self.cols];
+        cells.fill_with(|| " ".to_string());
+        let mut col = 0;
+        let mut last = None;
+        let mut escapes = String::new();
+        for segment in ansi_segments(text) {
+            let text = match segment {
+                AnsiSegment::Escape(escape) => {
+                    if escape.starts_with("\x1b[") && escape.ends_with('m') {
+                        escapes.push_str(escape);
+                    }
+                    continue;
+                }
+                AnsiSegment::Text(text) => text,
+            };
+            let indices = if format.east_asian_width {
+                text.width_indices_cjk()
+            } else {
+                text.width_indices()
+            };
+            for (_, cluster, _) in indices {
+                let (cluster, width) = match cluster.chars().next() {
+                    Some('\t') => (" ", TABS.tab_size - col % TABS.tab_size),
+                    Some(c) if c.is_control() => ("\u{FFFD}", 1),
+                    _ => (cluster, format.width(cluster)),
+                };
+                if width == 0 {
+                    // Shown over the character before it
+                    if let Some(cell) = last {
+                        let cell: &mut String = &mut cells[cell];
+                        cell.push_str(&escapes);
+                        cell.push_str(cluster);
+                        escapes.clear();
+                    }
+                    continue;
+                }
+                if col + width > self.cols {
+                    return;
+                }
+                let fill = if cluster == " " { " " } else { "" };
+                cells[col] = format!("{}{}", escapes, cluster);
+                for cell in &mut cells[col + 1..col + width] {
+                    *cell = fill.to_string();
+                }
+                escapes.clear();
+                last = Some(col);
+                col += width;
+            }
+        }
+    }
+
+    /// Shows `row` in reverse video.
+    pub fn set_reverse(&mut self, row: usize, reverse: bool) {
+        self.reverse[row] = reverse;
+    }
+
+    #[allow(unused)]
+    pub fn is_reverse(&self, row: usize) -> bool {
+        self.reverse[row]
+    }
+
+    /// The text on `row` without escape sequences or trailing spaces.
+    #[allow(unused)]
+    pub fn row_text(&self, row: usize) -> String {
+        let mut text = String::new();
+        for cell in &self.cells[row * self.cols..(row + 1) * self.cols] {
+            for segment in ansi_segments(cell) {
+                if let AnsiSegment::Text(cell_text) = segment {
+                    text.push_str(cell_text);
+                }
+            }
+        }
+        text.truncate(text.trim_end_matches(' ').len());
+        text
+    }
+
+    /// The output that puts the screen on the terminal, each row ending
+    /// with its colours reset.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for row in 0..self.rows {
+            let _ = write!(out, "\x1b[{};1H", row + 1);
+            if self.reverse[row] {
+                out.push_str("\x1b[7m");
+            }
+            for cell in &self.cells[row * self.cols..(row + 1) * self.cols] {
+                out.push_str(cell);
+            }
+            out.push_str("\x1b[0m");
+        }
+        out
+    }
+}
+
+/// What the pager does after a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+    Quit,
+}
+
+/// A search being typed after `/`.
+struct Search {
+    input: String,
+    /// Where the view was, to go back to when the search is cancelled
+    origin: usize,
+    following: bool,
+}
+
+/// The lines the pager keeps and the part of them it shows.
+pub struct Pager {
+    name: String,
+    size: u64,
+    format: LineFormat,
+    locale: Locale,
+    lines: VecDeque<String>,
+    scrollback: usize,
+    /// Text after the last line ending, completed by a later read
+    partial: String,
+    /// Index of the first line shown
+    top: usize,
+    /// Rows for lines, the screen less the status line
+    height: usize,
+    following: bool,
+    /// Why the file is no longer followed
+    failed: Option<String>,
+    search: Option<Search>,
+    query: String,
+    /// Index of the line the current match is on
+    found: Option<usize>,
+    /// When lines were appended, and how many
+    arrivals: VecDeque<(Instant, usize)>,
+}
+
+impl Pager {
+    /// A pager for the file `name`, `size` bytes long, keeping at most
+    /// `scrollback` lines laid out with `format`.
+    pub fn new(
+        name: &str,
+        size: u64,
+        scrollback: usize,
+        format: LineFormat,
+        locale: Locale,
+    ) -> Pager {
+        Pager {
+            name: name.to_string(),
+            size,
+            format,
+            locale,
+            lines: VecDeque::new(),
+            scrollback: scrollback.max(1),
+            partial: String::new(),
+            top: 0,
+            height: 1,
+            following: true,
+            failed: None,
+            search: None,
+            query: String::new(),
+            found: None,
+            arrivals: VecDeque::new(),
+        }
+    }
+
+    /// Adds the text that was in the file when the pager started.
+    pub fn load(&mut self, text: &str) {
+        self.push(text);
+        if !self.partial.is_empty() {
+            let line = std::mem::take(&mut self.partial);
+            self.push_lines(&line);
+        }
+    }
+
+    /// Adds text read from the file at `now`, counting its lines towards
+    /// the line rate.
+    pub fn append(&mut self, text: &str, now: Instant) {
+        let count = self.push(text);
+        if count > 0 {
+            self.arrivals.push_back((now, count));
+        }
+    }
+
+    /// Stops following the file, which can no longer be read, showing
+    /// `why` in the status line. The lines read so far can still be browsed.
+    pub fn fail(&mut self, why: &str) {
+        self.failed = Some(why.to_string());
+    }
+
+    /// Sets the file size shown in the status line.
+    pub fn set_size(&mut self, size: u64) {
+        self.size = size;
+    }
+
+    #[allow(unused)]
+    pub fn lines(&self) -> &VecDeque<String> {
+        &self.lines
+    }
+
+    #[allow(unused)]
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    #[allow(unused)]
+    pub fn is_following(&self) -> bool {
+        self.following
+    }
+
+    /// Adds the complete lines of `text`, returning how many.
+    fn push(&mut self, text: &str) -> usize {
+        self.partial.push_str(text);
+        let Some(end) = self.partial.rfind('\n') else {
+            return 0;
+        };
+        let rest = self.partial.split_off(end + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+        self.push_lines(&complete)
+    }
+
+    fn push_lines(&mut self, text: &str) -> usize {
+        let text = self.format.apply(text);
+        let mut count = 0;
+        for line in text.lines() {
+            self.lines.push_back(line.to_string());
+            count += 1;
+        }
+        let dropped = self.lines.len().saturating_sub(self.scrollback);
+        self.lines.drain(..dropped);
+        self.top = self.top.saturating_sub(dropped);
+        self.found = self.found.and_then(|found| found.checked_sub(dropped));
+        if let Some(search) = &mut self.search {
+            search.origin = search.origin.saturating_sub(dropped);
+        }
+        if self.following {
+            self.top = self.max_top();
+        }
+        count
+    }
+
+    fn max_top(&self) -> usize {
+        self.lines.len().saturating_sub(self.height)
+    }
+
+    fn scroll_to(&mut self, top: usize) {
+        self.top = top.min(self.max_top());
+    }
+
+    /// Scrolls up to `top`, pausing so that new lines don't move the view.
+    fn scroll_back_to(&mut self, top: usize) {
+        self.following = false;
+        self.scroll_to(top);
+    }
+
+    /// Brings the line at `index` into view.
+    fn show(&mut self, index: usize) {
+        self.following = false;
+        if index < self.top || index >= self.top + self.height {
+            self.scroll_to(index);
+        }
+    }
+
+    /// Handles `key`, returning whether to go on.
+    pub fn handle_key(&mut self, key: Key) -> Action {
+        if self.search.is_some() {
+            self.handle_search_key(key);
+            return Action::Continue;
+        }
+        match key {
+            Key::Char('q') | Key::CtrlC => return Action::Quit,
+            Key::Char('j') | Key::Down | Key::Enter => self.scroll_to(self.top + 1),
+            Key::Char('k') | Key::Up => self.scroll_back_to(self.top.saturating_sub(1)),
+            Key::Char(' ') | Key::PageDown => self.scroll_to(self.top + self.height),
+            Key::Char('b') | Key::PageUp => {
+                self.scroll_back_to(self.top.saturating_sub(self.height))
+            }
+            Key::Char('g') | Key::Home => self.scroll_back_to(0),
+            Key::Char('G') | Key::End => self.scroll_to(self.max_top()),
+            Key::Char('f') => {
+                self.following = !self.following;
+                if self.following {
+                    self.scroll_to(self.max_top());
+                }
+            }
+            Key::Char('/') => {
+                self.search = Some(Search {
+                    input: String::new(),
+                    origin: self.top,
+                    following: self.following,
+                });
+            }
+            Key::Char('n') => {
+                let from = self.found.map_or(self.top, |found| found + 1);
+                if let Some(found) = self.find(&self.query, from, true) {
+                    self.found = Some(found);
+                    self.show(found);
+                }
+            }
+            Key::Char('N') => {
+                let from = self.found.unwrap_or(self.top);
+                if let Some(found) = from
+                    .checked_sub(1)
+                    .and_then(|from| self.find(&self.query, from, false))
+                {
+                    self.found = Some(found);
+                    self.show(found);
+                }
+            }
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    fn handle_search_key(&mut self, key: Key) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        match key {
+            Key::Char(c) => search.input.push(c),
+            Key::Backspace => {
+                search.input.pop();
+            }
+            Key::Enter => {
+                self.query = std::mem::take(&mut search.input);
+                self.search = None;
+                return;
+            }
+            Key::Esc | Key::CtrlC => {
+                let (origin, following) = (search.origin, search.following);
+                self.search = None;
+                self.found = None;
+                self.following = following;
+                self.scroll_to(if following { self.max_top() } else { origin });
+                return;
+            }
+            _ => return,
+        }
+        // Search again from where the search started on every change.
+        let (input, origin) = (search.input.clone(), search.origin);
+        self.found = if input.is_empty() {
+            None
+        } else {
+            self.find(&input, origin, true)
+        };
+        match self.found {
+            Some(found) => self.show(found),
+            None => self.scroll_to(origin),
+        }
+    }
+
+    /// The index of the first line from `from` on, or back from it when
+    /// not `forward`, that contains `query`. The search ignores case when
+    /// `query` is all lowercase.
+    fn find(&self, query: &str, from: usize, forward: bool) -> Option<usize> {
+        if query.is_empty() {
+            return None;
+        }
+        let ignore_case = !query.chars().any(char::is_uppercase);
+        let query = if ignore_case {
+            query.to_lowercase()
+        } else {
+            query.to_string()
+        };
+        let matches = |index: &usize| {
+            let mut text = String::new();
+            for segment in ansi_segments(&self.lines[*index]) {
+                if let AnsiSegment::Text(line_text) = segment {
+                    text.push_str(line_text);
+                }
+            }
+            if ignore_case {
+                text = text.to_lowercase();
+            }
+            text.contains(&query)
+        };
+        if forward {
+            (from..self.lines.len()).find(matches)
+        } else {
+            (0..=from.min(self.lines.len().checked_sub(1)?))
+                .rev()
+                .find(matches)
+        }
+    }
+
+    /// Lines appended per second over the last [`RATE_WINDOW`] before
+    /// `now`.
+    fn rate(&self, now: Instant) -> f64 {
+        let lines: usize = self
+            .arrivals
+            .iter()
+            .filter(|(at, _)| now.saturating_duration_since(*at) < RATE_WINDOW)
+            .map(|(_, count)| count)
+            .sum();
+        lines as f64 / RATE_WINDOW.as_secs_f64()
+    }
+
+    /// Draws the lines in view and the status line on `screen`, which sets
+    /// how many lines are in view.
+    pub fn render(&mut self, screen: &mut Screen, now: Instant) {
+        while self
+            .arrivals
+            .front()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) >= RATE_WINDOW)
+        {
+            self.arrivals.pop_front();
+        }
+        let Some(status_row) = screen.rows().checked_sub(1) else {
+            return;
+        };
+        self.height = status_row.max(1);
+        if self.following {
+            self.top = self.max_top();
+        } else {
+            self.scroll_to(self.top);
+        }
+        for row in 0..status_row {
+            let index = self.top + row;
+            if let Some(line) = self.lines.get(index) {
+                screen.draw(row, line, &self.format);
+            }
+            screen.set_reverse(row, self.found == Some(index));
+        }
+        let status = self.status(screen.cols, now);
+        screen.draw(status_row, &status, &self.format);
+        screen.set_reverse(status_row, true);
+    }
+
+    /// The status line, `cols` columns wide: the search being typed, or the
+    /// file name, size and line rate with the follow state and position on
+    /// the right. The name is shortened from the front to keep the right
+    /// part on screen, and left out if even that is too wide.
+    fn status(&self, cols: usize, now: Instant) -> String {
+        if let Some(search) = &self.search {
+            let mut status = format!("/{}", search.input);
+            if !search.input.is_empty() && self.found.is_none() {
+                status.push_str("  ");
+                status.push_str(message(self.locale, MsgId::PagerNotFound));
+            }
+            return status;
+        }
+        let rate = format!("{:.1}", self.rate(now));
+        let info = format!(
+            "  {}  {}",
+            format_size(self.size),
+            tr(self.locale, MsgId::PagerRate, &[&rate])
+        );
+        let state = match &self.failed {
+            Some(why) => tr(self.locale, MsgId::PagerStopped, &[why]),
+            None if self.following => message(self.locale, MsgId::PagerFollowing).to_string(),
+            None => message(self.locale, MsgId::PagerPaused).to_string(),
+        };
+        let total = self.lines.len();
+        let last = (self.top + self.height).min(total);
+        let first = if total == 0 { 0 } else { self.top + 1 };
+        let right = format!("{}  {}-{}/{}", state, first, last, total);
+        let room = cols.saturating_sub(self.format.width(&info) + self.format.width(&right) + 2);
+        let left = match self.shorten_front(&self.name, room) {
+            Some(name) => format!("{}{}", name, info),
+            None => String::new(),
+        };
+        let gap = cols
+            .saturating_sub(self.format.width(&left) + self.format.width(&right))
+            .max(2);
+        format!("{}{}{}", left, " ".repeat(gap), right)
+    }
+
+    /// `name` if it fits in `max` columns, or else its end after `…`.
+    /// `None` if not even `…` fits.
+    fn shorten_front(&self, name: &str, max: usize) -> Option<String> {
+        if self.format.width(name) <= max {
+            return Some(name.to_string());
+        }
+        let room = max.checked_sub(self.format.width("…"))?;
+        let start = name
+            .char_indices()
+            .map(|(i, _)| i)
+            .find(|&i| self.format.width(&name[i..]) <= room)
+            .unwrap_or(name.len());
+        Some(format!("…{}", &name[start..]))
+    }
+}
+
+/// `bytes` in the largest binary unit that keeps it at least 1.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Something for the pager to handle.
+enum Event {
+    Keys(Vec<Key>),
+    Text(String),
+    /// The file can no longer be followed
+    Failed(String),
+}
+
+/// Runs the pager on the file at `path` until the user quits, starting
+/// with its last `count` lines. Keys are read from the controlling
+/// terminal, and the screen is drawn on stdout, which must be a terminal.
+#[cfg(unix)]
+pub fn run(
+    path: &Path,
+    count: u64,
+    scrollback: usize,
+    interval: Duration,
+    format: LineFormat,
+    locale: Locale,
+) -> io::Result<()> {
+    use std::{
+        fs::{File, OpenOptions},
+        io::{BufReader, Read},
+        sync::mpsc::channel,
+        thread,
+    };
+
+    use crate::global_fn::{follow_file, read_tail};
+
+    if terminal::terminal_size().is_none() {
+        return Err(io::Error::other("stdout is not a terminal"));
+    }
+    let file = OpenOptions::new().read(true).open(path)?;
+    let size = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+    let mut pager = Pager::new(
+        &path.display().to_string(),
+        size,
+        scrollback,
+        format,
+        locale,
+    );
+    pager.load(&read_tail(&mut reader, size, count)?);
+
+    let tty = File::open("/dev/tty")?;
+    let _raw = terminal::RawMode::enable(&tty)?;
+    let (tx, rx) = channel();
+    let keys = tx.clone();
+    let mut input = tty.try_clone()?;
+    thread::spawn(move || {
+        let mut buf = [0; 64];
+        while let Ok(read) = input.read(&mut buf) {
+            if read == 0 || keys.send(Event::Keys(parse_keys(&buf[..read]))).is_err() {
+                break;
+            }
+        }
+    });
+    let watched = path.to_path_buf();
+    thread::spawn(move || {
+        let followed = follow_file(&mut reader, &watched, size, interval, |text| {
+            tx.send(Event::Text(text.to_string())).is_ok()
+        });
+        if let Err(why) = followed {
+            let _ = tx.send(Event::Failed(why.to_string()));
+        }
+    });
+
+    let mut out = io::stdout().lock();
+    // Switch to the alternate screen and hide the cursor, and back after.
+    out.write_all(b"\x1b[?1049h\x1b[?25l")?;
+    let result = event_loop(&mut pager, &rx, path, &mut out);
+    out.write_all(b"\x1b[?25h\x1b[?1049l")?;
+    out.flush()?;
+    result
+}
+
+#[cfg(not(unix))]
+pub fn run(
+    _path: &Path,
+    _count: u64,
+    _scrollback: usize,
+    _interval: Duration,
+    _format: LineFormat,
+    _locale: Locale,
+) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "not supported on this platform",
+    ))
+}
+
+/// Redraws the screen after each batch of events until the user quits.
+#[cfg_attr(not(unix), allow(unused))]
+fn event_loop(
+    pager: &mut Pager,
+    events: &Receiver<Event>,
+    path: &Path,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    use std::{fs, sync::mpsc::RecvTimeoutError};
+
+    let handle = |pager: &mut Pager, event| match event {
+        Event::Keys(keys) => keys
+            .into_iter()
+            .any(|key| pager.handle_key(key) == Action::Quit),
+        Event::Text(text) => {
+            pager.append(&text, Instant::now());
+            if let Ok(metadata) = fs::metadata(path) {
+                pager.set_size(metadata.len());
+            }
+            false
+        }
+        Event::Failed(why) => {
+            pager.fail(&why);
+            false
+        }
+    };
+    loop {
+        let (cols, rows) = terminal::terminal_size().unwrap_or((80, 24));
+        let mut screen = Screen::new(cols, rows);
+        pager.render(&mut screen, Instant::now());
+        out.write_all(screen.to_ansi().as_bytes())?;
+        out.flush()?;
+        // Redraw at least every second for the line rate and the size of
+        // the terminal.
+        let event = match events.recv_timeout(Duration::from_secs(1)) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+        if handle(pager, event) {
+            return Ok(());
+        }
+        while let Ok(event) = events.try_recv() {
+            if handle(pager, event) {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -1,7 +1,8 @@
-use std::{error::Error, fmt, result};
+use std::{error::Error, ffi::OsString, fmt, path::PathBuf, result};
 
 use crate::{
-    global_fn::find_opt,
+    global_fn::{find_opt, resolve_vals},
+    i18n::{tr, Locale, MsgId},
     optgroup::{HasArg, Name, Occur},
 };
 
@@ -14,11 +15,31 @@ pub struct Matches {
     /// Values of the Options that matched and their positions
     pub vals: Vec<Vec<(usize, Optval)>>,
 
-    /// Free string fragments
+    /// Free string fragments, converted to UTF-8 lossily
     pub free: Vec<String>,
 
+    /// Free fragments exactly as given, e.g. non-UTF-8 file names
+    pub free_os: Vec<OsString>,
+
     /// Index of first free fragment after "--" separator
     pub args_end: Option<usize>,
+
+    /// Where the values of each option came from, `None` if it is unset
+    pub sources: Vec<Option<Source>>,
+
+    /// Name and matches of the subcommand, if one was given
+    pub subcommand: Option<(String, Box<Matches>)>,
+}
+
+/// Where the value of an option came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// Given on the command line
+    CommandLine,
+    /// Read from the named environment variable
+    Env(String),
+    /// Read from the config file at this path
+    Config(PathBuf),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -44,19 +65,34 @@ pub struct Opt {
 //     }
 // }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum Fail {
     ArgumentMissing(String),
-    UnrecognizedOption(String),
+    /// The option as given and the closest registered option, if any
+    UnrecognizedOption(String, Option<String>),
     OptionMissing(String),
     OptionDuplicated(String),
     UnexpectedArgument(String),
+    ConflictingOptions(String, String),
+    OptionRequires(String, String),
+    OneOfMissing(Vec<String>),
+    /// Where the setting was read from and its unknown key
+    UnknownSetting(String, String),
+    /// The prefix as given and the long options it matches
+    AmbiguousOption(String, Vec<String>),
+    /// The response file and why it could not be read
+    BadResponseFile(String, String),
+    UnterminatedQuote(String),
+    /// The option, as given, whose argument is not valid UTF-8
+    InvalidUtf8Argument(String),
 }
 
 #[derive(Clone)]
 pub enum Optval {
     Val(String),
     Given,
+    /// Turned off by `--no-<name>`
+    Negated,
 }
 
 impl Error for Fail {
@@ -75,24 +111,48 @@ impl Error for Fail {
     // fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {}
 }
 
-impl fmt::Display for Fail {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl Fail {
+    /// Describe the failure in `locale`.
+    pub fn message(&self, locale: Locale) -> String {
         use Fail::*;
         match *self {
-            ArgumentMissing(ref nm) => write!(f, "Argument to option '{}' missing", *nm),
-            UnrecognizedOption(ref nm) => write!(f, "Unrecognized option: '{}'", *nm),
-            OptionMissing(ref nm) => write!(f, "Required option '{}' missing", *nm),
-            OptionDuplicated(ref nm) => write!(f, "Option '{}' given more than once", *nm),
-            UnexpectedArgument(ref nm) => write!(f, "Option '{}' does not take an argument", *nm),
+            ArgumentMissing(ref nm) => tr(locale, MsgId::ArgumentMissing, &[nm]),
+            UnrecognizedOption(ref nm, None) => tr(locale, MsgId::UnrecognizedOption, &[nm]),
+            UnrecognizedOption(ref nm, Some(ref suggestion)) => tr(
+                locale,
+                MsgId::UnrecognizedOptionSuggestion,
+                &[nm, suggestion],
+            ),
+            OptionMissing(ref nm) => tr(locale, MsgId::OptionMissing, &[nm]),
+            OptionDuplicated(ref nm) => tr(locale, MsgId::OptionDuplicated, &[nm]),
+            UnexpectedArgument(ref nm) => tr(locale, MsgId::UnexpectedArgument, &[nm]),
+            ConflictingOptions(ref a, ref b) => tr(locale, MsgId::ConflictingOptions, &[a, b]),
+            OptionRequires(ref nm, ref req) => tr(locale, MsgId::OptionRequires, &[nm, req]),
+            OneOfMissing(ref nms) => tr(locale, MsgId::OneOfMissing, &[&nms.join("', '")]),
+            UnknownSetting(ref from, ref key) => tr(locale, MsgId::UnknownSetting, &[from, key]),
+            AmbiguousOption(ref nm, ref candidates) => tr(
+                locale,
+                MsgId::AmbiguousOption,
+                &[nm, &candidates.join("', '")],
+            ),
+            BadResponseFile(ref path, ref why) => tr(locale, MsgId::BadResponseFile, &[path, why]),
+            UnterminatedQuote(ref path) => tr(locale, MsgId::UnterminatedQuote, &[path]),
+            InvalidUtf8Argument(ref nm) => tr(locale, MsgId::InvalidUtf8Argument, &[nm]),
         }
     }
 }
 
+impl fmt::Display for Fail {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message(Locale::from_env()))
+    }
+}
+
 #[allow(unused)]
 impl Matches {
     fn opt_vals(&self, nm: &str) -> Vec<(usize, Optval)> {
         match find_opt(&self.opts, &Name::from_str(nm)) {
-            Some(id) => self.vals[id].clone(),
+            Some(id) => resolve_vals(&self.opts[id], &self.vals[id]),
             None => panic!("No option '{}' defined", nm),
         }
     }
@@ -109,12 +169,35 @@ impl Matches {
     fn opt_val(&self, nm: &str) -> Option<Optval> {
         self.opt_vals(nm).into_iter().map(|(_, o)| o).next()
     }
+
+    /// Returns where the value of option `nm` came from, or `None` if it
+    /// was not given anywhere.
+    pub fn opt_source(&self, nm: &str) -> Option<Source> {
+        match find_opt(&self.opts, &Name::from_str(nm)) {
+            Some(id) => self.sources[id].clone(),
+            None => panic!("No option '{}' defined", nm),
+        }
+    }
+
+    /// Returns the name and matches of the subcommand that was given.
+    pub fn subcommand(&self) -> Option<(&str, &Matches)> {
+        self.subcommand
+            .as_ref()
+            .map(|(name, matches)| (name.as_str(), matches.as_ref()))
+    }
+
     fn clone(&self) -> Self {
         Matches {
             opts: self.opts.clone(),
             vals: self.vals.clone(),
             free: self.free.clone(),
+            free_os: self.free_os.clone(),
             args_end: self.args_end,
+            sources: self.sources.clone(),
+            subcommand: self
+                .subcommand
+                .as_ref()
+                .map(|(name, matches)| (name.clone(), Box::new(Matches::clone(matches)))),
         }
     }
 }
@@ -0,0 +1,165 @@
+//! Machine-readable description of the options, printed by `--help=json`.
+//!
+//! The output is a JSON object whose `schema_version` is `SCHEMA_VERSION`.
+//! Within a version fields are only ever added; renaming or removing one, or
+//! changing what it means, bumps the version.
+
+use crate::{
+    arg_options::Options,
+    optgroup::{Constraint, HasArg, Occur, OptGroup},
+};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `s` as a JSON string, or `null` if it is empty.
+fn json_name(s: &str) -> String {
+    if s.is_empty() {
+        "null".to_string()
+    } else {
+        json_str(s)
+    }
+}
+
+fn json_opt(s: Option<&str>) -> String {
+    s.map_or("null".to_string(), json_str)
+}
+
+/// Lay out already rendered `items` as a JSON array at `indent`.
+fn json_array(items: &[String], indent: usize) -> String {
+    if items.is_empty() {
+        return "[]".to_string();
+    }
+    let pad = " ".repeat(indent + 2);
+    let items = items
+        .iter()
+        .map(|item| format!("{}{}", pad, item))
+        .collect::<Vec<String>>()
+        .join(",\n");
+    format!("[\n{}\n{}]", items, " ".repeat(indent))
+}
+
+/// Lay out already rendered `fields` as a JSON object at `indent`.
+fn json_object(fields: &[(&str, String)], indent: usize) -> String {
+    let pad = " ".repeat(indent + 2);
+    let fields = fields
+        .iter()
+        .map(|(key, value)| format!("{}{}: {}", pad, json_str(key), value))
+        .collect::<Vec<String>>()
+        .join(",\n");
+    format!("{{\n{}\n{}}}", fields, " ".repeat(indent))
+}
+
+fn has_arg_name(hasarg: &HasArg) -> &'static str {
+    match *hasarg {
+        HasArg::Yes => "yes",
+        HasArg::No => "no",
+        HasArg::Maybe => "maybe",
+    }
+}
+
+fn occur_name(occur: &Occur) -> &'static str {
+    match *occur {
+        Occur::Req => "required",
+        Occur::Optional => "optional",
+        Occur::Multi => "multi",
+        Occur::LastWins => "last_wins",
+    }
+}
+
+impl Options {
+    /// Describe the options, their constraints and subcommands as JSON, see
+    /// the module documentation.
+    pub fn json_schema(&self) -> String {
+        let mut fields = vec![("schema_version", SCHEMA_VERSION.to_string())];
+        fields.extend(self.json_fields(2));
+        format!("{}\n", json_object(&fields, 0))
+    }
+
+    fn json_fields(&self, indent: usize) -> Vec<(&'static str, String)> {
+        let options = self
+            .grps
+            .iter()
+            .map(|grp| self.json_group(grp, indent + 2))
+            .collect::<Vec<String>>();
+        let constraints = self
+            .constraints
+            .iter()
+            .map(|constraint| {
+                let (kind, names) = match *constraint {
+                    Constraint::ConflictsWith(ref a, ref b) => ("conflicts_with", vec![a, b]),
+                    Constraint::Requires(ref nm, ref req) => ("requires", vec![nm, req]),
+                    Constraint::OneOf(ref nms) => ("one_of", nms.iter().collect()),
+                };
+                let names = names.iter().map(|nm| json_str(nm)).collect::<Vec<String>>();
+                json_object(
+                    &[
+                        ("kind", json_str(kind)),
+                        ("options", json_array(&names, indent + 4)),
+                    ],
+                    indent + 2,
+                )
+            })
+            .collect::<Vec<String>>();
+        let subcommands = self
+            .subcommands
+            .iter()
+            .map(|sub| {
+                let mut fields = vec![("name", json_str(&sub.name)), ("desc", json_str(&sub.desc))];
+                fields.extend(sub.options.json_fields(indent + 4));
+                json_object(&fields, indent + 2)
+            })
+            .collect::<Vec<String>>();
+        vec![
+            ("options", json_array(&options, indent)),
+            ("constraints", json_array(&constraints, indent)),
+            ("subcommands", json_array(&subcommands, indent)),
+        ]
+    }
+
+    fn json_group(&self, grp: &OptGroup, indent: usize) -> String {
+        let is_grp = |nm: &String| *nm == grp.short_name || *nm == grp.long_name;
+        let env = self
+            .env_vars
+            .iter()
+            .find(|(nm, _)| is_grp(nm))
+            .map(|(_, var)| var.as_str());
+        let default = self
+            .defaults
+            .iter()
+            .find(|(nm, _)| is_grp(nm))
+            .map(|(_, value)| value.as_str());
+        json_object(
+            &[
+                ("short", json_name(&grp.short_name)),
+                ("long", json_name(&grp.long_name)),
+                ("hint", json_name(&grp.hint)),
+                ("desc", json_str(&grp.desc)),
+                ("has_arg", json_str(has_arg_name(&grp.hasarg))),
+                ("occur", json_str(occur_name(&grp.occur))),
+                ("negatable", grp.negatable.to_string()),
+                ("section", json_opt(grp.section.as_deref())),
+                ("default", json_opt(default)),
+                ("env", json_opt(env)),
+            ],
+            indent,
+        )
+    }
+}
@@ -0,0 +1,105 @@
+use std::env;
+
+/// The widest terminal taken into account; wider values are capped to it.
+pub const MAX_COLUMNS: usize = 4096;
+
+/// Parse a `COLUMNS` value, ignoring anything that isn't a positive number
+/// and capping it at [`MAX_COLUMNS`].
+pub fn parse_columns(value: &str) -> Option<usize> {
+    match value.trim().parse::<usize>() {
+        Ok(0) | Err(_) => None,
+        Ok(cols) => Some(cols.min(MAX_COLUMNS)),
+    }
+}
+
+/// Returns the width of the terminal in columns, taken from `COLUMNS` or,
+/// failing that, from the terminal attached to stdout. Returns `None` when
+/// stdout is not a terminal.
+pub fn terminal_width() -> Option<usize> {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|value| parse_columns(&value))
+        .or_else(ioctl_width)
+}
+
+/// Returns the size of the terminal attached to stdout as columns and
+/// rows, or `None` when stdout is not a terminal.
+pub fn terminal_size() -> Option<(usize, usize)> {
+    ioctl_size()
+}
+
+fn ioctl_width() -> Option<usize> {
+    ioctl_size().map(|(cols, _)| cols)
+}
+
+#[cfg(unix)]
+fn ioctl_size() -> Option<(usize, usize)> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: TIOCGWINSZ only writes a `winsize` through the pointer, which
+    // points at a live, properly aligned `winsize`.
+    let ret = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    if ret == 0 && size.ws_col > 0 && size.ws_row > 0 {
+        Some((usize::from(size.ws_col), usize::from(size.ws_row)))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn ioctl_size() -> Option<(usize, usize)> {
+    None
+}
+
+/// Keeps a terminal in raw mode, where keys are read one at a time without
+/// echo or line editing, restoring its settings when dropped.
+#[cfg(unix)]
+pub struct RawMode {
+    fd: std::os::unix::io::RawFd,
+    saved: libc::termios,
+}
+
+#[cfg(unix)]
+impl RawMode {
+    /// Switches the terminal `tty` to raw mode. `tty` must stay open until
+    /// the `RawMode` is dropped.
+    pub fn enable(tty: &std::fs::File) -> std::io::Result<RawMode> {
+        use std::{io, mem::MaybeUninit, os::unix::io::AsRawFd};
+
+        let fd = tty.as_raw_fd();
+        let mut saved = MaybeUninit::uninit();
+        // SAFETY: tcgetattr initialises the `termios` it is given when it
+        // returns 0, and `saved` is only read after that.
+        let saved = unsafe {
+            if libc::tcgetattr(fd, saved.as_mut_ptr()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            saved.assume_init()
+        };
+        let mut raw = saved;
+        // SAFETY: `raw` is a valid `termios`, which cfmakeraw only changes
+        // and tcsetattr only reads.
+        if unsafe {
+            libc::cfmakeraw(&mut raw);
+            libc::tcsetattr(fd, libc::TCSANOW, &raw)
+        } != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(RawMode { fd, saved })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: `saved` is the `termios` tcgetattr returned for `fd`.
+        unsafe {
+            libc::tcsetattr(self.fd, libc::TCSANOW, &self.saved);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{
+    arg_options::Options,
+    completion::{generate, Shell},
+};
+
+fn fruit_options() -> Options {
+    let mut opts = Options::new();
+    opts.optopt("n", "", "number of fruits", "NUMS");
+    opts.optopt("o", "output", "write fruits to FILE", "FILE");
+    opts.optflag("k", "kiwi", "it's a kiwi");
+    opts.optflagopt("h", "help", "print help", "FORMAT");
+    opts
+}
+
+#[test]
+fn test_shell_from_str() {
+    assert_eq!(Shell::from_str("bash"), Some(Shell::Bash));
+    assert_eq!(Shell::from_str("zsh"), Some(Shell::Zsh));
+    assert_eq!(Shell::from_str("fish"), Some(Shell::Fish));
+    assert_eq!(Shell::from_str("tcsh"), None);
+}
+
+#[test]
+fn test_bash_completion() {
+    let script = generate(Shell::Bash, "fruits", &fruit_options());
+    assert!(script.contains("compgen -W \"-n -o --output -k --kiwi -h --help\""));
+    assert!(script.contains("        -n)\n            return 0\n"));
+    assert!(script
+        .contains("        -o|--output)\n            COMPREPLY=( $(compgen -f -- \"$cur\") )\n"));
+    assert!(script.ends_with("complete -F _fruits fruits\n"));
+}
+
+#[test]
+fn test_zsh_completion() {
+    let script = generate(Shell::Zsh, "fruits", &fruit_options());
+    assert!(script.starts_with("#compdef fruits\n"));
+    assert!(script.contains("'-n[number of fruits]:NUMS: '"));
+    assert!(script.contains("'(-o --output)'{-o,--output}'[write fruits to FILE]:FILE:_files'"));
+    assert!(script.contains("'(-k --kiwi)'{-k,--kiwi}'[it'\\''s a kiwi]'"));
+    assert!(script.contains("'(-h --help)-h[print help]' \\\n"));
+    assert!(script.contains("'(-h --help)--help=-[print help]::FORMAT: '"));
+    assert!(!script.contains("'(-h --help)'{-h,--help}"));
+}
+
+#[test]
+fn test_fish_completion() {
+    let script = generate(Shell::Fish, "fruits", &fruit_options());
+    let expected = "complete -c fruits -s n -x -d 'number of fruits'
+complete -c fruits -s o -l output -r -F -d 'write fruits to FILE'
+complete -c fruits -s k -l kiwi -d 'it\\'s a kiwi'
+complete -c fruits -s h -l help -d 'print help'
+";
+    assert_eq!(script, expected);
+}
@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    arg_options::Options,
+    config::{Config, ConfigError},
+    result_error::{Fail, Source},
+};
+
+fn options() -> Options {
+    let mut opts = Options::new();
+    opts.optopt("n", "lines", "number of lines", "NUMS");
+    opts.optopt("c", "bytes", "number of bytes", "NUMS");
+    opts.optflag("f", "follow", "follow");
+    opts.conflicts_with("c", "n");
+    opts.env_var("lines", "RTAIL_LINES");
+    opts.env_var("follow", "RTAIL_FOLLOW");
+    opts
+}
+
+fn config(text: &str) -> Config {
+    Config::parse(text, Path::new("config.toml")).unwrap()
+}
+
+#[test]
+fn test_config_parse() {
+    let config = config(
+        "# defaults\n\
+         lines = 20 # trailing comment\n\
+         \"sleep-interval\" = '0.5'\n\
+         \n\
+         follow = true\n\
+         name = \"a # b\"\n",
+    );
+    assert_eq!(config.get("lines"), Some("20"));
+    assert_eq!(config.get("sleep-interval"), Some("0.5"));
+    assert_eq!(config.get("follow"), Some("true"));
+    assert_eq!(config.get("name"), Some("a # b"));
+    assert_eq!(config.get("bytes"), None);
+}
+
+#[test]
+fn test_config_parse_errors() {
+    let path = Path::new("config.toml");
+    for (text, line) in [
+        ("[rtail]\nlines = 1", 1),
+        ("lines = 1\nlines", 2),
+        ("lines = 1\nlines = 2", 2),
+        ("lines = twenty", 1),
+        (" = 1", 1),
+    ] {
+        match Config::parse(text, path) {
+            Err(ConfigError::Syntax(_, n, _)) => assert_eq!(n, line, "{:?}", text),
+            r => panic!("{:?}: {:?}", text, r),
+        }
+    }
+}
+
+#[test]
+fn test_config_load_missing() {
+    let path = PathBuf::from("/nonexistent/rtail/config.toml");
+    assert_eq!(Config::load(&path).unwrap(), None);
+}
+
+#[test]
+fn test_defaults_precedence() {
+    let opts = options();
+    let config = config("lines = 30\nfollow = true");
+    let env = |var: &str| (var == "RTAIL_LINES").then(|| "20".to_string());
+
+    let m = opts
+        .parse_with_defaults(["-n", "5"], env, Some(&config))
+        .unwrap();
+    assert_eq!(m.opt_str("n").unwrap(), "5");
+    assert_eq!(m.opt_source("lines"), Some(Source::CommandLine));
+
+    let m = opts
+        .parse_with_defaults(Vec::<String>::new(), env, Some(&config))
+        .unwrap();
+    assert_eq!(m.opt_str("lines").unwrap(), "20");
+    assert_eq!(
+        m.opt_source("n"),
+        Some(Source::Env("RTAIL_LINES".to_string()))
+    );
+    assert!(m.opt_present("f"));
+    assert_eq!(
+        m.opt_source("f"),
+        Some(Source::Config(PathBuf::from("config.toml")))
+    );
+
+    let m = opts
+        .parse_with_defaults(Vec::<String>::new(), |_| None, Some(&config))
+        .unwrap();
+    assert_eq!(m.opt_str("n").unwrap(), "30");
+
+    let m = opts.parse_with_defaults(["file"], |_| None, None).unwrap();
+    assert!(!m.opt_present("n"));
+    assert_eq!(m.opt_source("n"), None);
+    assert_eq!(m.free, ["file"]);
+}
+
+#[test]
+fn test_defaults_skip_conflicts() {
+    let opts = options();
+    let config = config("lines = 30");
+    let m = opts
+        .parse_with_defaults(["-c", "100"], |_| None, Some(&config))
+        .unwrap();
+    assert_eq!(m.opt_str("c").unwrap(), "100");
+    assert!(!m.opt_present("n"));
+}
+
+#[test]
+fn test_defaults_flag_values() {
+    let opts = options();
+    let env =
+        |value: &'static str| move |var: &str| (var == "RTAIL_FOLLOW").then(|| value.to_string());
+    let no_args = Vec::<String>::new();
+    assert!(opts
+        .parse_with_defaults(&no_args, env("yes"), None)
+        .unwrap()
+        .opt_present("f"));
+    assert!(!opts
+        .parse_with_defaults(&no_args, env("0"), None)
+        .unwrap()
+        .opt_present("f"));
+    match opts.parse_with_defaults(&no_args, env("maybe"), None) {
+        Err(Fail::UnexpectedArgument(nm)) => assert_eq!(nm, "follow"),
+        Ok(_) => panic!("expected an error"),
+        Err(f) => panic!("{:?}", f),
+    }
+}
+
+#[test]
+fn test_unknown_setting() {
+    let opts = options();
+    let config = config("lines = 1\ncolour = true");
+    match opts.parse_with_defaults(Vec::<String>::new(), |_| None, Some(&config)) {
+        Err(Fail::UnknownSetting(from, key)) => {
+            assert_eq!(from, "config.toml");
+            assert_eq!(key, "colour");
+        }
+        Ok(_) => panic!("expected an error"),
+        Err(f) => panic!("{:?}", f),
+    }
+}
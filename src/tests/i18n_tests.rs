@@ -0,0 +1,67 @@
+use crate::{
+    arg_options::Options,
+    i18n::{fill, message, tr, Locale, MsgId},
+    result_error::Fail,
+};
+
+#[test]
+fn test_locale_from_name() {
+    assert_eq!(Locale::from_name("ko_KR.UTF-8"), Locale::Ko);
+    assert_eq!(Locale::from_name("ko"), Locale::Ko);
+    assert_eq!(Locale::from_name("en_US.UTF-8"), Locale::En);
+    assert_eq!(Locale::from_name("C"), Locale::En);
+    assert_eq!(Locale::from_name(""), Locale::En);
+}
+
+#[test]
+fn test_fill() {
+    assert_eq!(fill("'{0}' and '{1}'", &["a", "b"]), "'a' and 'b'");
+    assert_eq!(fill("{1}{0}", &["a", "b"]), "ba");
+    // Arguments are not expanded again, unknown placeholders are kept.
+    assert_eq!(fill("{0} {1}", &["{1}", "b"]), "{1} b");
+    assert_eq!(fill("{2} {x} {", &["a"]), "{2} {x} {");
+}
+
+#[test]
+fn test_fail_message() {
+    let fail = Fail::ConflictingOptions("c".to_string(), "n".to_string());
+    assert_eq!(
+        fail.message(Locale::En),
+        "Options 'c' and 'n' cannot be used together"
+    );
+    assert_eq!(
+        fail.message(Locale::Ko),
+        "옵션 'c'와(과) 'n'은(는) 함께 쓸 수 없습니다"
+    );
+    let fail = Fail::OneOfMissing(vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(
+        fail.message(Locale::En),
+        "One of the options 'a', 'b' is required"
+    );
+}
+
+#[test]
+fn test_usage_brief() {
+    assert_eq!(
+        tr(Locale::Ko, MsgId::UsageBrief, &["rtail"]),
+        "사용법: rtail [옵션] FILE"
+    );
+}
+
+#[test]
+fn test_usage_east_asian_width() {
+    // U+2192 RIGHTWARDS ARROW is ambiguous: one column wide, two in CJK.
+    let mut opts = Options::new();
+    opts.optflag("k", "kiwi\u{2192}", message(Locale::Ko, MsgId::HelpLines));
+    opts.optflag("o", "orange", "→ 오렌지");
+    opts.east_asian_width(true);
+
+    let expected = "Usage: fruits
+
+Options:
+    -k, --kiwi→        출력할 줄 수
+    -o, --orange        → 오렌지
+";
+
+    assert_eq!(opts.usage("Usage: fruits"), expected);
+}
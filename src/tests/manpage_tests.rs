@@ -0,0 +1,66 @@
+use crate::{arg_options::Options, i18n::Locale, manpage::Page, rtail_options};
+
+fn fruit_page() -> Page<'static> {
+    Page {
+        name: "fruits",
+        about: "count fruit-shaped things",
+        synopsis: "[options] BASKET",
+        examples: &[("fruits -k basket.txt", "Count only the kiwis.")],
+    }
+}
+
+fn fruit_options() -> Options {
+    let mut opts = Options::new();
+    opts.optopt("n", "", "number of fruits", "NUMS");
+    opts.section("Citrus");
+    opts.optflag("o", "orange", ".dot at the start\nof a line");
+    opts
+}
+
+#[test]
+fn test_man_page() {
+    let expected = ".TH FRUITS 1
+.SH NAME
+fruits \\- count fruit\\-shaped things
+.SH SYNOPSIS
+.B fruits
+[options] BASKET
+.SH OPTIONS
+.TP
+\\fB\\-n\\fR \\fINUMS\\fR
+number of fruits
+.SS Citrus
+.TP
+\\fB\\-o\\fR, \\fB\\-\\-orange\\fR
+\\&.dot at the start
+of a line
+.SH EXAMPLES
+.TP
+\\fBfruits \\-k basket.txt\\fR
+Count only the kiwis.
+";
+    assert_eq!(fruit_options().man_page(&fruit_page()), expected);
+}
+
+#[test]
+fn test_markdown() {
+    let markdown = fruit_options().markdown(&fruit_page());
+    assert!(markdown.starts_with("# fruits\n\n## NAME\n\nfruits - count fruit-shaped things\n"));
+    assert!(markdown.contains("## SYNOPSIS\n\n```\nfruits [options] BASKET\n```\n"));
+    assert!(markdown.contains(&format!(
+        "## OPTIONS\n\n```\n{}\n```\n",
+        fruit_options().usage_sections()
+    )));
+    assert!(markdown.ends_with("```bash\n$ fruits -k basket.txt\n```\n\nCount only the kiwis.\n"));
+}
+
+#[test]
+fn test_readme_usage_is_current() {
+    let readme = include_str!("../../README.md");
+    let usage = rtail_options(Locale::En).usage("Usage: rtail [options] FILE");
+    assert!(
+        readme.contains(&usage),
+        "README.md usage block is out of date, expected:\n{}",
+        usage
+    );
+}
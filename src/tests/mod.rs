@@ -1,18 +1,30 @@
 use crate::{
-    arg_options::Options,
-    global_fn::each_split_within,
+    arg_options::{usage_columns, Options},
+    global_fn::{
+        each_split_within, edit_distance, read_tail, split_args, split_within, take_utf8_lossy,
+        LineFormat,
+    },
+    i18n::Locale,
     optgroup::{HasArg, Name, Occur},
-    result_error::Opt,
+    result_error::{Fail, Opt},
+    terminal::{parse_columns, MAX_COLUMNS},
 };
+use unicode_width::Profile;
 
+mod completion_tests;
+mod config_tests;
+mod i18n_tests;
+mod manpage_tests;
 mod notify;
+mod pager_tests;
+mod schema_tests;
 mod unicode_tests;
 
 #[cfg(test)]
 #[test]
 fn test_split_within() {
     fn t(s: &str, i: usize, u: &[String]) {
-        let v = each_split_within(&(s.to_string()), i);
+        let v = each_split_within(s, i);
         assert!(v.iter().zip(u.iter()).all(|(a, b)| a == b));
     }
     t("", 0, &[]);
@@ -29,7 +41,7 @@ fn test_split_within() {
     );
     t(
         "\nMary had a little lamb\nLittle lamb\n",
-        ::std::usize::MAX,
+        usize::MAX,
         &[
             "Mary had a little lamb".to_string(),
             "Little lamb".to_string(),
@@ -37,6 +49,94 @@ fn test_split_within() {
     );
 }
 
+#[test]
+fn test_split_within_indent() {
+    let fit = |s: &str, _, max| unicode_width::truncate_to_width(s, max, None).text().len();
+    let split = |s, lim, indent| split_within(s, lim, indent, true, &fit);
+    let tabs = |s: &str, column, max| LineFormat::default().fit(s, column, max);
+
+    assert_eq!(split("", 10, 2), [""]);
+    assert_eq!(split("fits", 4, 2), ["fits"]);
+    // A tab reaches to the next tab stop, counted from the indent on later rows
+    assert_eq!(
+        split_within("\tat foo.bar.baz qux", 20, 0, true, &tabs),
+        ["\tat", "foo.bar.baz qux"]
+    );
+    assert_eq!(
+        split_within("aaaaaaaaaa bb\tcc", 12, 4, true, &tabs),
+        ["aaaaaaaaaa", "bb\tcc"]
+    );
+    assert_eq!(split("a  b   c", 4, 0), ["a  b", "c"]);
+    assert_eq!(
+        split("  leading spaces stay", 10, 0),
+        ["  leading", "spaces", "stay"]
+    );
+    assert_eq!(
+        split("한국어 문장을 나눕니다", 8, 2),
+        ["한국어", "문장을", "나눕니", "다"]
+    );
+    assert_eq!(
+        split("👩\u{200D}🔬👩\u{200D}🔬 ok", 3, 0),
+        ["👩\u{200D}🔬", "👩\u{200D}🔬", "ok"]
+    );
+    // A character wider than a row still makes progress
+    assert_eq!(split("한a", 1, 0), ["한", "a"]);
+    assert_eq!(
+        split_within("a verylongword b", 5, 0, false, &fit),
+        ["a", "verylongword", "b"]
+    );
+}
+
+#[test]
+fn test_line_format_wrap() {
+    let wrap = |indent| LineFormat {
+        wrap: Some(12),
+        wrap_indent: indent,
+        ..LineFormat::default()
+    };
+    let text = "short\nERROR disk full on /var\r\n\n";
+    assert_eq!(wrap(0).apply(text), "short\nERROR disk\nfull on /var\r\n\n");
+    assert_eq!(
+        wrap(4).apply(text),
+        "short\nERROR disk\n    full on\n    /var\r\n\n"
+    );
+    assert_eq!(
+        wrap(2).apply("\x1b[31mERROR\x1b[0m disk full\n"),
+        "\x1b[31mERROR\x1b[0m disk\n  full\n"
+    );
+    assert_eq!(wrap(0).apply("\tat foo bar\n"), "\tat\nfoo bar\n");
+    // An indent wider than half a row is cut down to half
+    let narrow = LineFormat {
+        wrap: Some(10),
+        wrap_indent: 12,
+        ..LineFormat::default()
+    };
+    let rows = narrow.apply("ERROR disk full on /var/log\n");
+    assert_eq!(
+        rows,
+        "ERROR disk\n     full\n     on\n     /var/\n     log\n"
+    );
+    assert!(rows.lines().all(|row| row.len() <= 10), "{:?}", rows);
+    assert_eq!(LineFormat::default().fit("a\tb", 3, 5), 2);
+    assert_eq!(LineFormat::default().fit("a\tb", 3, 6), 3);
+}
+
+#[test]
+fn test_line_format_chop_tabs() {
+    let chop = |max| LineFormat {
+        chop: Some(max),
+        ..LineFormat::default()
+    };
+    let line = format!("\t\t\t\t{}\n", "x".repeat(46));
+    assert_eq!(chop(40).apply(&line), "\t\t\t\txxxxxxx…\n");
+    assert_eq!(chop(12).apply("\tab\tcd\n"), "\tab…\n");
+    assert_eq!(chop(9).apply("a\tb\n"), "a\tb\n");
+    assert_eq!(
+        chop(10).apply("\t\x1b[31mERROR\x1b[0m\n"),
+        "\t\x1b[31mE…\x1b[0m\n"
+    );
+}
+
 // Tests for reqopt
 #[test]
 fn test_reqopt() {
@@ -135,20 +235,20 @@ fn test_optopt() {
     }
 }
 
-// #[test]
-// fn test_optopt_missing() {
-//     let args = vec!["blah".to_string()];
-//     match Options::new()
-//         .optopt("t", "test", "testing", "TEST")
-//         .parse(&args)
-//     {
-//         Ok(ref m) => {
-//             assert!(!m.opt_present("test"));
-//             assert!(!m.opt_present("t"));
-//         }
-//         _ => panic!(),
-//     }
-// }
+#[test]
+fn test_optopt_missing() {
+    let args = vec!["blah".to_string()];
+    match Options::new()
+        .optopt("t", "test", "testing", "TEST")
+        .parse(&args)
+    {
+        Ok(ref m) => {
+            assert!(!m.opt_present("test"));
+            assert!(!m.opt_present("t"));
+        }
+        _ => panic!(),
+    }
+}
 
 // #[test]
 // fn test_optopt_no_arg() {
@@ -548,6 +648,46 @@ fn test_free_argument_is_hyphen() {
 //     }
 // }
 
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance("", ""), 0);
+    assert_eq!(edit_distance("follow", "follow"), 0);
+    assert_eq!(edit_distance("folow", "follow"), 1);
+    assert_eq!(edit_distance("follwo", "follow"), 1);
+    assert_eq!(edit_distance("", "abc"), 3);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+}
+
+#[test]
+fn test_unrecognized_option_suggestion() {
+    let mut opts = Options::new();
+    opts.optflag("f", "follow", "Desc");
+    opts.optopt("n", "", "Desc", "NUMS");
+    match opts.parse(["--folow"]) {
+        Err(e @ Fail::UnrecognizedOption(..)) => assert_eq!(
+            e.message(Locale::En),
+            "Unrecognized option '--folow'; did you mean '--follow'?"
+        ),
+        _ => panic!(),
+    }
+    // A long option written with a single dash.
+    match opts.parse(["-follwo"]) {
+        Err(Fail::UnrecognizedOption(nm, suggestion)) => {
+            assert_eq!(nm, "-o");
+            assert_eq!(suggestion.as_deref(), Some("--follow"));
+        }
+        _ => panic!(),
+    }
+    match opts.parse(["-z"]) {
+        Err(Fail::UnrecognizedOption(nm, None)) => assert_eq!(nm, "-z"),
+        _ => panic!(),
+    }
+    match opts.parse(["--banana"]) {
+        Err(Fail::UnrecognizedOption(nm, None)) => assert_eq!(nm, "--banana"),
+        _ => panic!(),
+    }
+}
+
 // #[test]
 // fn test_combined() {
 //     let args = vec![
@@ -1186,6 +1326,517 @@ Options:
 //     assert!(matches.opt_present("i"));
 // }
 
+#[test]
+fn test_usage_sections() {
+    let mut opts = Options::new();
+    opts.optflag("k", "kiwi", "Desc");
+    opts.section("Citrus");
+    opts.optflag("o", "orange", "Desc");
+    opts.optflag("l", "lemon", "Desc");
+
+    let expected = "Usage: fruits
+
+Options:
+    -k, --kiwi          Desc
+
+Citrus:
+    -o, --orange        Desc
+    -l, --lemon         Desc
+";
+
+    assert_eq!(opts.usage("Usage: fruits"), expected);
+}
+
+#[test]
+fn test_subcommand() {
+    let mut opts = Options::new();
+    opts.optflag("v", "verbose", "Desc");
+    opts.subcommand("stats", "Print statistics")
+        .optopt("n", "lines", "Desc", "NUMS");
+    opts.subcommand("follow", "Follow a file");
+
+    let m = opts.parse(["-v", "stats", "-n", "5", "log"]).unwrap();
+    assert!(m.opt_present("v"));
+    assert!(m.free.is_empty());
+    let (name, sub) = m.subcommand().unwrap();
+    assert_eq!(name, "stats");
+    assert_eq!(sub.opt_str("n").unwrap(), "5");
+    assert_eq!(sub.free, ["log"]);
+
+    // Options after the subcommand name belong to the subcommand.
+    match opts.parse(["follow", "-v"]) {
+        Err(Fail::UnrecognizedOption(nm, _)) => assert_eq!(nm, "-v"),
+        _ => panic!(),
+    }
+
+    // Only the first free argument names a subcommand.
+    let m = opts.parse(["log", "stats"]).unwrap();
+    assert!(m.subcommand().is_none());
+    assert_eq!(m.free, ["log", "stats"]);
+    let m = opts.parse(["--", "stats"]).unwrap();
+    assert!(m.subcommand().is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_non_utf8_free_argument() {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+    // "로그.txt" in EUC-KR
+    let name = OsString::from_vec(b"\xb7\xce\xb1\xd7.txt".to_vec());
+    let mut opts = Options::new();
+    opts.optopt("n", "lines", "Desc", "NUMS");
+
+    let m = opts
+        .parse([OsString::from("-n"), OsString::from("5"), name.clone()])
+        .unwrap();
+    assert_eq!(m.opt_str("n").unwrap(), "5");
+    assert_eq!(m.free_os, vec![name.clone()]);
+    assert_eq!(m.free, [name.to_string_lossy()]);
+
+    let m = opts.parse([OsString::from("--"), name.clone()]).unwrap();
+    assert_eq!(m.free_os, vec![name.clone()]);
+
+    // Options and their arguments still have to be UTF-8.
+    let mut opt = OsString::from("--");
+    opt.push(&name);
+    assert_eq!(
+        opts.parse([opt]).err().unwrap(),
+        Fail::UnrecognizedOption(format!("--{}", name.to_string_lossy()), None)
+    );
+    let fail = opts
+        .parse([OsString::from("-n"), name.clone()])
+        .err()
+        .unwrap();
+    assert_eq!(fail, Fail::InvalidUtf8Argument("-n".to_string()));
+    assert_eq!(
+        fail.message(Locale::En),
+        "Argument to option '-n' is not valid UTF-8"
+    );
+    assert_eq!(
+        opts.parse([OsString::from("--lines"), name.clone()])
+            .err()
+            .unwrap(),
+        Fail::InvalidUtf8Argument("--lines".to_string())
+    );
+    let mut opt = OsString::from("--lines=");
+    opt.push(&name);
+    assert_eq!(
+        opts.parse([opt]).err().unwrap(),
+        Fail::InvalidUtf8Argument("--lines".to_string())
+    );
+    let mut opt = OsString::from("-n");
+    opt.push(&name);
+    assert_eq!(
+        opts.parse([opt]).err().unwrap(),
+        Fail::InvalidUtf8Argument("-n".to_string())
+    );
+}
+
+#[test]
+fn test_long_prefixes() {
+    let mut opts = Options::new();
+    opts.optflag("f", "follow", "Desc");
+    opts.optopt("", "format", "Desc", "FMT");
+    opts.optopt("", "lines", "Desc", "NUMS");
+    opts.optflag("", "line-numbers", "Desc");
+
+    // Prefixes are off by default.
+    assert!(opts.parse(["--foll"]).is_err());
+
+    opts.long_prefixes(true);
+    let m = opts
+        .parse(["--foll", "--form=json", "--lines", "3"])
+        .unwrap();
+    assert!(m.opt_present("follow"));
+    assert_eq!(m.opt_str("format").unwrap(), "json");
+    // An exact match wins over longer options with the same prefix.
+    assert_eq!(m.opt_str("lines").unwrap(), "3");
+    assert!(!m.opt_present("line-numbers"));
+
+    match opts.parse(["--fo=x"]) {
+        Err(Fail::AmbiguousOption(nm, candidates)) => {
+            assert_eq!(nm, "--fo");
+            assert_eq!(candidates, ["--follow", "--format"]);
+        }
+        _ => panic!(),
+    }
+    match opts.parse(["--fx"]) {
+        Err(Fail::UnrecognizedOption(nm, _)) => assert_eq!(nm, "--fx"),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_split_args() {
+    assert_eq!(
+        split_args("-n 5\n  --follow 'my log.txt'").unwrap(),
+        ["-n", "5", "--follow", "my log.txt"]
+    );
+    assert_eq!(
+        split_args(r#"a"b c"d "\"\\\x" e\ f '' # comment"#).unwrap(),
+        ["ab cd", r#""\\x"#, "e f", ""]
+    );
+    assert_eq!(
+        split_args("# only\n\n# comments\n").unwrap(),
+        Vec::<String>::new()
+    );
+    assert_eq!(split_args("'open"), None);
+    assert_eq!(split_args("\"open"), None);
+}
+
+#[test]
+fn test_line_format_chop() {
+    let text = "short\nthis line is too long\r\n한국어 줄\nno newline";
+    assert_eq!(LineFormat::default().apply(text), text);
+
+    let chop = LineFormat {
+        chop: Some(8),
+        east_asian_width: false,
+        ..LineFormat::default()
+    };
+    assert_eq!(chop.apply(text), "short\nthis li…\r\n한국어 …\nno newl…");
+
+    let chop = LineFormat {
+        chop: Some(4),
+        east_asian_width: true,
+        ..LineFormat::default()
+    };
+    assert_eq!(chop.apply("ab\n“x”\n"), "ab\n“…\n");
+
+    let chop = LineFormat {
+        chop: Some(6),
+        east_asian_width: false,
+        ..LineFormat::default()
+    };
+    assert_eq!(
+        chop.apply("\x1b[31mERROR\x1b[0m disk full\n\x1b[32mOK\x1b[0m\n"),
+        "\x1b[31mERROR\x1b[0m…\n\x1b[32mOK\x1b[0m\n"
+    );
+}
+
+#[test]
+fn test_line_format_profile() {
+    let text = "👍🏽 thumbs
+";
+    let chop = |profile| LineFormat {
+        chop: Some(5),
+        profile,
+        ..LineFormat::default()
+    };
+    assert_eq!(chop(Profile::Kitty).apply(text), "👍🏽 t…\n");
+    assert_eq!(chop(Profile::Xterm).apply(text), "👍🏽…\n");
+}
+
+#[test]
+fn test_read_tail() {
+    let path = std::env::temp_dir().join(format!("rtail-tail-{}.txt", std::process::id()));
+    let tail = |content: &[u8], count| {
+        std::fs::write(&path, content).unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let mut reader = std::io::BufReader::new(file);
+        read_tail(&mut reader, content.len() as u64, count).unwrap()
+    };
+    assert_eq!(tail(b"", 10), "");
+    assert_eq!(tail(b"x", 1), "x");
+    assert_eq!(tail(b"\n", 1), "\n");
+    assert_eq!(tail(b"a\nb\nc\n", 2), "b\nc\n");
+    assert_eq!(tail(b"a\nb\nc", 2), "b\nc");
+    assert_eq!(tail(b"a\nb\n", 5), "a\nb\n");
+    assert_eq!(tail(b"a\n\n", 1), "\n");
+    assert_eq!(tail(b"a\nb\n", 0), "");
+    assert_eq!(tail(b"\xffa\n", 1), "\u{FFFD}a\n");
+    // Lines spread over several reads
+    let lines: String = (1..=1000).map(|n| format!("line {}\n", n)).collect();
+    let last: String = (401..=1000).map(|n| format!("line {}\n", n)).collect();
+    assert_eq!(tail(lines.as_bytes(), 600), last);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_take_utf8_lossy() {
+    let mut bytes = b"a\xed\x95".to_vec();
+    assert_eq!(take_utf8_lossy(&mut bytes), "a");
+    assert_eq!(bytes, b"\xed\x95");
+    bytes.extend_from_slice(b"\x9c b");
+    assert_eq!(take_utf8_lossy(&mut bytes), "한 b");
+    assert!(bytes.is_empty());
+    let mut bytes = b"\xffb\xff".to_vec();
+    assert_eq!(take_utf8_lossy(&mut bytes), "\u{FFFD}b\u{FFFD}");
+    assert!(bytes.is_empty());
+}
+
+#[test]
+fn test_response_files() {
+    let path = std::env::temp_dir().join(format!("rtail-args-{}.txt", std::process::id()));
+    std::fs::write(&path, "-n 5\n'my log.txt'\n").unwrap();
+    let arg = format!("@{}", path.display());
+
+    let mut opts = Options::new();
+    opts.optopt("n", "lines", "Desc", "NUMS");
+    opts.optflag("f", "follow", "Desc");
+    assert_eq!(opts.parse([arg.as_str()]).unwrap().free, [arg.as_str()]);
+
+    opts.response_files(true);
+    let m = opts.parse(["-f", arg.as_str(), "other"]).unwrap();
+    assert!(m.opt_present("f"));
+    assert_eq!(m.opt_str("n").unwrap(), "5");
+    assert_eq!(m.free, ["my log.txt", "other"]);
+
+    let m = opts.parse(["--", arg.as_str()]).unwrap();
+    assert_eq!(m.free, [arg.as_str()]);
+    std::fs::remove_file(&path).unwrap();
+
+    match opts.parse([arg.as_str()]) {
+        Err(Fail::BadResponseFile(nm, _)) => assert_eq!(nm, path.display().to_string()),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_optflag_negatable() {
+    let mut opts = Options::new();
+    opts.optflag_negatable("c", "color", "Desc");
+
+    assert!(opts.parse(["--color"]).unwrap().opt_present("color"));
+    assert!(!opts.parse(["--no-color"]).unwrap().opt_present("c"));
+    assert!(!opts.parse(["-c", "--no-color"]).unwrap().opt_present("c"));
+    assert!(opts.parse(["--no-color", "-c"]).unwrap().opt_present("c"));
+    assert!(!opts.parse(Vec::<String>::new()).unwrap().opt_present("c"));
+    match opts.parse(["--no-color=yes"]) {
+        Err(Fail::UnexpectedArgument(_)) => {}
+        _ => panic!(),
+    }
+
+    // `--no-` only applies to negatable flags.
+    opts.optflag("v", "verbose", "Desc");
+    match opts.parse(["--no-verbose"]) {
+        Err(Fail::UnrecognizedOption(nm, _)) => assert_eq!(nm, "--no-verbose"),
+        _ => panic!(),
+    }
+
+    // `--no-color` overrides a default from the environment.
+    opts.env_var("color", "COLOR");
+    let env = |_: &str| Some("true".to_string());
+    let m = opts.parse_with_defaults(["--no-color"], env, None).unwrap();
+    assert!(!m.opt_present("c"));
+    let m = opts
+        .parse_with_defaults(Vec::<String>::new(), env, None)
+        .unwrap();
+    assert!(m.opt_present("c"));
+}
+
+#[test]
+fn test_optflag_negatable_prefix() {
+    let mut opts = Options::new();
+    opts.optflag_negatable("f", "follow", "Desc");
+    opts.optflag("", "force", "Desc");
+    opts.long_prefixes(true);
+
+    assert!(opts.parse(["--foll"]).unwrap().opt_present("f"));
+    assert!(!opts.parse(["-f", "--no-foll"]).unwrap().opt_present("f"));
+    assert!(!opts.parse(["-f", "--no"]).unwrap().opt_present("f"));
+    match opts.parse(["--fo"]) {
+        Err(Fail::AmbiguousOption(_, found)) => assert_eq!(found, ["--follow", "--force"]),
+        _ => panic!(),
+    }
+    // `--no-` is only offered for negatable flags.
+    match opts.parse(["--no-forc"]) {
+        Err(Fail::UnrecognizedOption(nm, _)) => assert_eq!(nm, "--no-forc"),
+        _ => panic!(),
+    }
+    match opts.parse(["--no-folow"]) {
+        Err(Fail::UnrecognizedOption(nm, suggestion)) => {
+            assert_eq!(nm, "--no-folow");
+            assert_eq!(suggestion.as_deref(), Some("--no-follow"));
+        }
+        _ => panic!(),
+    }
+
+    // Without prefixes, negations are still suggested.
+    opts.long_prefixes(false);
+    match opts.parse(["--no-foll"]) {
+        Err(Fail::UnrecognizedOption(_, suggestion)) => {
+            assert_eq!(suggestion.as_deref(), Some("--no-follow"))
+        }
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_last_wins() {
+    let mut opts = Options::new();
+    opts.optopt("n", "lines", "Desc", "NUMS");
+    match opts.parse(["-n", "1", "-n", "2"]) {
+        Err(Fail::OptionDuplicated(nm)) => assert_eq!(nm, "lines"),
+        _ => panic!(),
+    }
+
+    opts.last_wins("n");
+    let m = opts.parse(["-n", "1", "--lines=2", "-n3"]).unwrap();
+    assert_eq!(m.opt_str("n").unwrap(), "3");
+}
+
+#[test]
+fn test_usage_negatable() {
+    let mut opts = Options::new();
+    opts.optflag_negatable("c", "color", "Desc");
+
+    let expected = "Usage: fruits
+
+Options:
+    -c, --[no-]color    Desc
+";
+
+    assert_eq!(opts.usage("Usage: fruits"), expected);
+}
+
+#[test]
+fn test_usage_ansi() {
+    let mut opts = Options::new();
+    opts.optopt("k", "kiwi", "\x1b[1mDesc\x1b[0m", "\x1b[4mN\x1b[0m");
+    opts.optflag("a", "apple", "Desc");
+
+    let expected = "Usage: fruits
+
+Options:
+    -k, --kiwi \x1b[4mN\x1b[0m        \x1b[1mDesc\x1b[0m
+    -a, --apple         Desc
+";
+
+    assert_eq!(opts.usage("Usage: fruits"), expected);
+}
+
+#[test]
+fn test_usage_subcommands() {
+    let mut opts = Options::new();
+    opts.optflag("k", "kiwi", "Desc");
+    opts.subcommand("peel", "Peel the fruit");
+    opts.subcommand("slice-into-wedges", "Slice the fruit");
+
+    let expected = "Usage: fruits
+
+Options:
+    -k, --kiwi          Desc
+
+Commands:
+    peel                Peel the fruit
+    slice-into-wedges   Slice the fruit
+";
+
+    assert_eq!(opts.usage("Usage: fruits"), expected);
+}
+
+#[test]
+fn test_usage_columns() {
+    assert_eq!(usage_columns(80), (24, 54));
+    assert_eq!(usage_columns(120), (36, 82));
+    assert_eq!(usage_columns(40), (16, 22));
+    assert_eq!(usage_columns(0), (16, 20));
+    assert_eq!(usage_columns(usize::MAX), (40, usize::MAX - 42));
+}
+
+#[test]
+fn test_usage_term_width() {
+    let mut opts = Options::new();
+    opts.optflag("k", "kiwi", "kiwi description that is long enough to wrap");
+    opts.optflag("c", "cjk", "키위 설명은 좁은 터미널에서 줄바꿈이 됩니다");
+    opts.term_width(44);
+
+    let expected = "Usage: fruits
+
+Options:
+    -k, --kiwi  kiwi description that is
+                long enough to wrap
+    -c, --cjk   키위 설명은 좁은
+                터미널에서 줄바꿈이 됩니다
+";
+
+    assert_eq!(opts.usage("Usage: fruits"), expected);
+}
+
+#[test]
+fn test_parse_columns() {
+    assert_eq!(parse_columns("132"), Some(132));
+    assert_eq!(parse_columns(" 80\n"), Some(80));
+    assert_eq!(parse_columns("18446744073709551615"), Some(MAX_COLUMNS));
+    assert_eq!(parse_columns("0"), None);
+    assert_eq!(parse_columns("wide"), None);
+}
+
+// Tests for constraints
+#[test]
+fn test_conflicts_with() {
+    let mut opts = Options::new();
+    opts.optopt("n", "", "lines", "NUMS");
+    opts.optopt("c", "", "bytes", "NUMS");
+    opts.conflicts_with("c", "n");
+    assert!(opts.parse(["-n", "1"]).is_ok());
+    assert!(opts.parse(["-c", "1"]).is_ok());
+    match opts.parse(["-n", "1", "-c", "2"]) {
+        Err(Fail::ConflictingOptions(a, b)) => assert_eq!((a.as_str(), b.as_str()), ("-c", "-n")),
+        _ => panic!(),
+    }
+
+    let mut opts = Options::new();
+    opts.optopt("n", "lines", "lines", "NUMS");
+    opts.optopt("c", "bytes", "bytes", "NUMS");
+    opts.optflag("", "chop", "chop");
+    opts.optflag("", "wrap", "wrap");
+    opts.conflicts_with("c", "n");
+    opts.conflicts_with("chop", "wrap");
+    match opts.parse(["--lines", "1", "-c", "2"]) {
+        Err(e) => assert_eq!(
+            e.message(Locale::En),
+            "Options '-c/--bytes' and '-n/--lines' cannot be used together"
+        ),
+        _ => panic!(),
+    }
+    match opts.parse(["--wrap", "--chop"]) {
+        Err(e) => assert_eq!(
+            e.message(Locale::En),
+            "Options '--chop' and '--wrap' cannot be used together"
+        ),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_requires() {
+    let mut opts = Options::new();
+    opts.optflag("f", "follow", "follow");
+    opts.optopt("", "pid", "pid", "PID");
+    opts.requires("pid", "f");
+    assert!(opts.parse(["--follow", "--pid=1"]).is_ok());
+    assert!(opts.parse(["-f"]).is_ok());
+    match opts.parse(["--pid", "1"]) {
+        Err(e @ Fail::OptionRequires(..)) => {
+            assert_eq!(
+                e.message(Locale::En),
+                "Option '--pid' requires option '-f/--follow'"
+            )
+        }
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_one_of() {
+    let mut opts = Options::new();
+    opts.optflag("a", "apple", "Desc");
+    opts.optflag("b", "banana", "Desc");
+    opts.one_of(&["a", "b"]);
+    assert!(opts.parse(["-a"]).is_ok());
+    match opts.parse(Vec::<String>::new()) {
+        Err(Fail::OneOfMissing(nms)) => assert_eq!(nms, vec!["-a/--apple", "-b/--banana"]),
+        _ => panic!(),
+    }
+    match opts.parse(["-a", "-b"]) {
+        Err(Fail::ConflictingOptions(..)) => {}
+        _ => panic!(),
+    }
+}
+
 #[test]
 #[should_panic]
 fn test_long_name_too_short() {
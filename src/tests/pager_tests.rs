@@ -0,0 +1,346 @@
+use std::time::{Duration, Instant};
+
+use unicode_width::Profile;
+
+use crate::{
+    global_fn::LineFormat,
+    i18n::Locale,
+    pager::{format_size, parse_keys, Action, Key, Pager, Screen},
+};
+
+fn pager(scrollback: usize) -> Pager {
+    Pager::new(
+        "app.log",
+        100,
+        scrollback,
+        LineFormat::default(),
+        Locale::En,
+    )
+}
+
+fn numbered(from: usize, to: usize) -> String {
+    (from..=to).map(|n| format!("line {}\n", n)).collect()
+}
+
+/// Renders `pager` on a `cols` by `rows` screen, returning its rows.
+fn render(pager: &mut Pager, cols: usize, rows: usize, now: Instant) -> (Screen, Vec<String>) {
+    let mut screen = Screen::new(cols, rows);
+    pager.render(&mut screen, now);
+    let text = (0..rows).map(|row| screen.row_text(row)).collect();
+    (screen, text)
+}
+
+fn press(pager: &mut Pager, keys: &str) {
+    for key in parse_keys(keys.as_bytes()) {
+        assert_eq!(pager.handle_key(key), Action::Continue);
+    }
+}
+
+#[test]
+fn test_parse_keys() {
+    assert_eq!(
+        parse_keys(b"q/a\r"),
+        [Key::Char('q'), Key::Char('/'), Key::Char('a'), Key::Enter]
+    );
+    assert_eq!(
+        parse_keys(b"\x1b[A\x1b[B\x1bOA\x1b[5~\x1b[6~\x1b[H\x1b[4~"),
+        [
+            Key::Up,
+            Key::Down,
+            Key::Up,
+            Key::PageUp,
+            Key::PageDown,
+            Key::Home,
+            Key::End
+        ]
+    );
+    assert_eq!(parse_keys(b"\x1b"), [Key::Esc]);
+    assert_eq!(parse_keys(b"\x7f\x03"), [Key::Backspace, Key::CtrlC]);
+    // Unused keys such as F5 are dropped.
+    assert_eq!(parse_keys(b"\x1b[15~j"), [Key::Char('j')]);
+    assert_eq!(parse_keys("한".as_bytes()), [Key::Char('한')]);
+}
+
+#[test]
+fn test_screen_draw() {
+    let format = LineFormat::default();
+    let mut screen = Screen::new(5, 4);
+    screen.draw(0, "ab한국", &format);
+    screen.draw(1, "한국어", &format);
+    screen.draw(2, "a\tb", &format);
+    screen.draw(3, "e\u{301}\x01x", &format);
+    assert_eq!(screen.row_text(0), "ab한");
+    assert_eq!(screen.row_text(1), "한국");
+    assert_eq!(screen.row_text(2), "a");
+    assert_eq!(screen.row_text(3), "e\u{301}\u{FFFD}x");
+
+    let mut screen = Screen::new(10, 1);
+    screen.draw(0, "a\tb", &format);
+    assert_eq!(screen.row_text(0), "a       b");
+    // Colours are kept, other escape sequences dropped.
+    screen.draw(0, "\x1b[2J\x1b[31mred\x1b[0m ok", &format);
+    assert_eq!(screen.row_text(0), "red ok");
+    assert_eq!(
+        screen.to_ansi(),
+        "\x1b[1;1H\x1b[31mred\x1b[0m ok    \x1b[0m"
+    );
+    // A wide character takes its cell and an empty one.
+    screen.draw(0, "한a", &format);
+    screen.set_reverse(0, true);
+    assert_eq!(screen.to_ansi(), "\x1b[1;1H\x1b[7m한a       \x1b[0m");
+}
+
+#[test]
+fn test_screen_draw_format() {
+    let mut screen = Screen::new(4, 1);
+    let format = LineFormat {
+        east_asian_width: true,
+        ..LineFormat::default()
+    };
+    screen.draw(0, "①②③", &format);
+    assert_eq!(screen.row_text(0), "①②");
+    screen.draw(0, "①②③", &LineFormat::default());
+    assert_eq!(screen.row_text(0), "①②③");
+
+    let format = LineFormat {
+        profile: Profile::Xterm,
+        ..LineFormat::default()
+    };
+    screen.draw(0, "👍🏽 ok", &LineFormat::default());
+    assert_eq!(screen.row_text(0), "👍🏽 o");
+    screen.draw(0, "👍🏽 ok", &format);
+    assert_eq!(screen.row_text(0), "👍🏽");
+}
+
+#[test]
+fn test_pager_follow() {
+    let now = Instant::now();
+    let mut pager = pager(100);
+    pager.load(&numbered(1, 5));
+    let (_, rows) = render(&mut pager, 60, 4, now);
+    assert_eq!(rows[..3], ["line 3", "line 4", "line 5"]);
+    assert!(rows[3].starts_with("app.log  100 B  0.0 lines/s"));
+    assert!(rows[3].ends_with("following  3-5/5"));
+
+    pager.append(&numbered(6, 6), now);
+    let (_, rows) = render(&mut pager, 60, 4, now);
+    assert_eq!(rows[..3], ["line 4", "line 5", "line 6"]);
+
+    // Scrolling back pauses, and new lines leave the view alone.
+    press(&mut pager, "k");
+    assert!(!pager.is_following());
+    pager.append(&numbered(7, 8), now);
+    let (_, rows) = render(&mut pager, 60, 4, now);
+    assert_eq!(rows[..3], ["line 3", "line 4", "line 5"]);
+    assert!(rows[3].ends_with("paused  3-5/8"));
+
+    press(&mut pager, "f");
+    let (_, rows) = render(&mut pager, 60, 4, now);
+    assert_eq!(rows[..3], ["line 6", "line 7", "line 8"]);
+    press(&mut pager, "f");
+    assert!(!pager.is_following());
+    pager.append(&numbered(9, 9), now);
+    let (_, rows) = render(&mut pager, 60, 4, now);
+    assert_eq!(rows[..3], ["line 6", "line 7", "line 8"]);
+}
+
+#[test]
+fn test_pager_scroll() {
+    let now = Instant::now();
+    let mut pager = pager(100);
+    pager.load(&numbered(1, 20));
+    render(&mut pager, 60, 6, now);
+    assert_eq!(pager.top(), 15);
+    press(&mut pager, "g");
+    assert_eq!(pager.top(), 0);
+    press(&mut pager, "k");
+    assert_eq!(pager.top(), 0);
+    press(&mut pager, " ");
+    assert_eq!(pager.top(), 5);
+    press(&mut pager, "j\x1b[B\r");
+    assert_eq!(pager.top(), 8);
+    press(&mut pager, "b");
+    assert_eq!(pager.top(), 3);
+    press(&mut pager, "\x1b[6~\x1b[6~\x1b[6~");
+    assert_eq!(pager.top(), 15);
+    press(&mut pager, "\x1b[H");
+    assert_eq!(pager.top(), 0);
+    press(&mut pager, "G");
+    assert_eq!(pager.top(), 15);
+    // Jumping to the end doesn't resume following.
+    assert!(!pager.is_following());
+
+    // A taller screen shows more lines, a shorter one keeps the top.
+    let (_, rows) = render(&mut pager, 60, 4, now);
+    assert_eq!(rows[0], "line 16");
+    let (_, rows) = render(&mut pager, 60, 30, now);
+    assert_eq!(rows[0], "line 1");
+    assert_eq!(rows[20], "");
+    assert!(rows[29].ends_with("paused  1-20/20"));
+
+    assert_eq!(pager.handle_key(Key::Char('q')), Action::Quit);
+    assert_eq!(pager.handle_key(Key::CtrlC), Action::Quit);
+}
+
+#[test]
+fn test_pager_scrollback() {
+    let now = Instant::now();
+    let mut pager = pager(5);
+    pager.load(&numbered(1, 8));
+    assert_eq!(pager.lines().len(), 5);
+    assert_eq!(pager.lines()[0], "line 4");
+
+    render(&mut pager, 60, 3, now);
+    press(&mut pager, "g");
+    pager.append(&numbered(9, 10), now);
+    // The view stays on the oldest line kept.
+    let (_, rows) = render(&mut pager, 60, 3, now);
+    assert_eq!(rows[..2], ["line 6", "line 7"]);
+    pager.append(&numbered(11, 11), now);
+    let (_, rows) = render(&mut pager, 60, 3, now);
+    assert_eq!(rows[..2], ["line 7", "line 8"]);
+}
+
+#[test]
+fn test_pager_partial_lines() {
+    let now = Instant::now();
+    let mut pager = pager(100);
+    pager.load("first\nunterminated");
+    assert_eq!(pager.lines(), &["first", "unterminated"]);
+    pager.append("ab", now);
+    assert_eq!(pager.lines().len(), 2);
+    pager.append("c\nd\r\ne", now);
+    assert_eq!(pager.lines(), &["first", "unterminated", "abc", "d"]);
+}
+
+#[test]
+fn test_pager_wraps_lines() {
+    let now = Instant::now();
+    let format = LineFormat {
+        wrap: Some(10),
+        ..LineFormat::default()
+    };
+    let mut pager = Pager::new("app.log", 0, 100, format, Locale::En);
+    pager.load("one two three four\n");
+    let (_, rows) = render(&mut pager, 10, 4, now);
+    assert_eq!(rows[..2], ["one two", "three four"]);
+}
+
+#[test]
+fn test_pager_search() {
+    let now = Instant::now();
+    let mut pager = pager(100);
+    let mut text = numbered(1, 20);
+    text = text.replace("line 4\n", "ERROR disk\n");
+    text = text.replace("line 12\n", "error \x1b[31mdisk\x1b[0m\n");
+    pager.load(&text);
+    render(&mut pager, 60, 4, now);
+    assert_eq!(pager.top(), 17);
+
+    // The search starts from the top of the view, so go to the first line.
+    press(&mut pager, "g/dis");
+    let (screen, rows) = render(&mut pager, 60, 4, now);
+    assert_eq!(rows[0], "ERROR disk");
+    assert!(screen.is_reverse(0));
+    assert!(!screen.is_reverse(1));
+    assert_eq!(rows[3], "/dis");
+
+    press(&mut pager, "\r");
+    let (_, rows) = render(&mut pager, 60, 4, now);
+    assert!(rows[3].starts_with("app.log"));
+    press(&mut pager, "n");
+    let (screen, rows) = render(&mut pager, 60, 4, now);
+    assert_eq!(rows[0], "error disk");
+    assert!(screen.is_reverse(0));
+    // No match after the last: stay.
+    press(&mut pager, "n");
+    assert_eq!(pager.top(), 11);
+    press(&mut pager, "N");
+    assert_eq!(pager.top(), 3);
+
+    // Lowercase searches ignore case, others don't.
+    press(&mut pager, "g/ERROR");
+    let (_, rows) = render(&mut pager, 60, 4, now);
+    assert_eq!(rows[0], "ERROR disk");
+    press(&mut pager, "\rn");
+    assert_eq!(pager.top(), 3);
+
+    // Nothing found, and cancelling, go back to where the search started.
+    press(&mut pager, "/zz");
+    let (_, rows) = render(&mut pager, 60, 4, now);
+    assert_eq!(rows[3], "/zz  not found");
+    assert_eq!(pager.top(), 3);
+    press(&mut pager, "\x7f\x7fline 9");
+    assert_eq!(pager.top(), 8);
+    press(&mut pager, "\x1b");
+    assert_eq!(pager.top(), 3);
+    let (screen, _) = render(&mut pager, 60, 4, now);
+    assert!(!screen.is_reverse(0));
+
+    // Cancelling a search started while following follows again.
+    press(&mut pager, "f/line 1");
+    assert!(!pager.is_following());
+    press(&mut pager, "\x1b");
+    assert!(pager.is_following());
+}
+
+#[test]
+fn test_pager_rate() {
+    let start = Instant::now();
+    let mut pager = pager(100);
+    pager.load(&numbered(1, 50));
+    pager.append(&numbered(51, 60), start);
+    pager.append(&numbered(61, 65), start + Duration::from_secs(5));
+    let (_, rows) = render(&mut pager, 60, 3, start + Duration::from_secs(6));
+    assert!(rows[2].starts_with("app.log  100 B  1.5 lines/s"));
+    let (_, rows) = render(&mut pager, 60, 3, start + Duration::from_secs(12));
+    assert!(rows[2].starts_with("app.log  100 B  0.5 lines/s"));
+    let (_, rows) = render(&mut pager, 60, 3, start + Duration::from_secs(20));
+    assert!(rows[2].starts_with("app.log  100 B  0.0 lines/s"));
+
+    pager.set_size(5 * 1024 * 1024 + 300 * 1024);
+    let (_, rows) = render(&mut pager, 60, 3, start);
+    assert!(rows[2].starts_with("app.log  5.3 MiB"));
+}
+
+#[test]
+fn test_pager_status_width() {
+    let now = Instant::now();
+    let mut pager = Pager::new("앱.log", 2048, 100, LineFormat::default(), Locale::Ko);
+    pager.load(&numbered(1, 3));
+    let (_, rows) = render(&mut pager, 50, 3, now);
+    assert_eq!(
+        rows[2],
+        "앱.log  2.0 KiB  초당 0.0줄     따라가는 중  2-3/3"
+    );
+    // The name gives way to the follow state and position.
+    let (_, rows) = render(&mut pager, 45, 3, now);
+    assert_eq!(rows[2], "…log  2.0 KiB  초당 0.0줄  따라가는 중  2-3/3");
+    let (_, rows) = render(&mut pager, 42, 3, now);
+    assert_eq!(rows[2], "…  2.0 KiB  초당 0.0줄  따라가는 중  2-3/3");
+    let (_, rows) = render(&mut pager, 20, 3, now);
+    assert_eq!(rows[2], "  따라가는 중  2-3/3");
+}
+
+#[test]
+fn test_pager_failed() {
+    let now = Instant::now();
+    let mut pager = pager(100);
+    pager.load(&numbered(1, 3));
+    pager.fail("file removed");
+    let (_, rows) = render(&mut pager, 60, 3, now);
+    assert!(rows[2].ends_with("stopped: file removed  2-3/3"));
+    press(&mut pager, "g");
+    let (_, rows) = render(&mut pager, 60, 3, now);
+    assert_eq!(rows[0], "line 1");
+}
+
+#[test]
+fn test_format_size() {
+    assert_eq!(format_size(0), "0 B");
+    assert_eq!(format_size(1023), "1023 B");
+    assert_eq!(format_size(1024), "1.0 KiB");
+    assert_eq!(format_size(1536), "1.5 KiB");
+    assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    assert_eq!(format_size(u64::MAX), "16777216.0 TiB");
+}
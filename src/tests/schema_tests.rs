@@ -0,0 +1,83 @@
+use crate::{arg_options::Options, schema::SCHEMA_VERSION};
+
+#[test]
+fn test_json_schema() {
+    let mut opts = Options::new();
+    opts.optopt("n", "lines", "number of \"lines\"", "NUMS");
+    opts.section("Misc");
+    opts.optflagopt("", "help", "print help", "FORMAT");
+    opts.requires("help", "n");
+    opts.default_value("n", "10");
+    opts.env_var("lines", "LINES");
+    opts.subcommand("stats", "print statistics");
+
+    let expected = r#"{
+  "schema_version": 1,
+  "options": [
+    {
+      "short": "n",
+      "long": "lines",
+      "hint": "NUMS",
+      "desc": "number of \"lines\"",
+      "has_arg": "yes",
+      "occur": "optional",
+      "negatable": false,
+      "section": null,
+      "default": "10",
+      "env": "LINES"
+    },
+    {
+      "short": null,
+      "long": "help",
+      "hint": "FORMAT",
+      "desc": "print help",
+      "has_arg": "maybe",
+      "occur": "optional",
+      "negatable": false,
+      "section": "Misc",
+      "default": null,
+      "env": null
+    }
+  ],
+  "constraints": [
+    {
+      "kind": "requires",
+      "options": [
+        "help",
+        "n"
+      ]
+    }
+  ],
+  "subcommands": [
+    {
+      "name": "stats",
+      "desc": "print statistics",
+      "options": [],
+      "constraints": [],
+      "subcommands": []
+    }
+  ]
+}
+"#;
+    assert_eq!(SCHEMA_VERSION, 1);
+    assert_eq!(opts.json_schema(), expected);
+}
+
+#[test]
+fn test_help_optional_format() {
+    let mut opts = Options::new();
+    opts.optflagopt("h", "help", "print help", "FORMAT");
+    let m = opts.parse(["--help"]).unwrap();
+    assert!(m.opt_present("h"));
+    assert_eq!(m.opt_str("help"), None);
+    let m = opts.parse(["--help=json"]).unwrap();
+    assert_eq!(m.opt_str("h").unwrap(), "json");
+    let m = opts.parse(["-h", "app.log"]).unwrap();
+    assert!(m.opt_present("h"));
+    assert_eq!(m.opt_str("h"), None);
+    assert_eq!(m.free, ["app.log"]);
+    let m = opts.parse(["--help", "json"]).unwrap();
+    assert_eq!(m.opt_str("h"), None);
+    assert_eq!(m.free, ["json"]);
+    assert!(opts.usage("").contains("-h, --help[=FORMAT]"));
+}
@@ -612,6 +612,10 @@ impl UnicodeWidthChar for Foo {
     fn width_cjk(self) -> Option<usize> {
         Some(0)
     }
+
+    fn width_with(self, _: &unicode_width::WidthOptions) -> Option<usize> {
+        Some(0)
+    }
 }
 
 #[cfg(feature = "cjk")]
@@ -623,4 +627,32 @@ impl UnicodeWidthStr for Foo {
     fn width_cjk(&self) -> usize {
         0
     }
+
+    fn width_indices(&self) -> unicode_width::WidthIndices<'_> {
+        "".width_indices()
+    }
+
+    fn width_indices_cjk(&self) -> unicode_width::WidthIndices<'_> {
+        "".width_indices_cjk()
+    }
+
+    fn width_ansi(&self) -> usize {
+        0
+    }
+
+    fn width_ansi_cjk(&self) -> usize {
+        0
+    }
+
+    fn width_for(&self, _: unicode_width::Profile) -> usize {
+        0
+    }
+
+    fn width_cjk_for(&self, _: unicode_width::Profile) -> usize {
+        0
+    }
+
+    fn width_with(&self, _: &unicode_width::WidthOptions) -> usize {
+        0
+    }
 }